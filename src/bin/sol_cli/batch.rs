@@ -0,0 +1,228 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    time::Instant,
+};
+
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::{
+    board::{Board, cmove::CMove},
+    solver::Solver,
+};
+
+use super::{
+    output::{Format, board_id, print_json},
+    parse_puzzle,
+};
+
+/// Solve many puzzles, one board id or board string per line
+#[derive(FromArgs)]
+#[argh(subcommand, name = "batch")]
+pub struct BatchArgs {
+    #[argh(positional)]
+    /// file to read the puzzles from. reads from stdin when not given. empty
+    /// lines and lines starting with # are skipped
+    file: Option<String>,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the results: text (a table, the default) or ndjson, one
+    /// line per puzzle followed by a summary
+    pub format: Format,
+}
+
+/// A puzzle that was read and solved.
+#[derive(Serialize)]
+struct Solved {
+    line: usize,
+    board: Board,
+    id: Option<String>,
+    solution_count: u64,
+
+    /// The first of the solver's solutions, which come in the same order on
+    /// every run.
+    first_solution: Option<Vec<CMove>>,
+    time_ms: f64,
+}
+
+/// A line that isn't a board id or board string.
+#[derive(Serialize)]
+struct Invalid {
+    line: usize,
+    input: String,
+    error: String,
+}
+
+#[derive(Serialize, Default)]
+struct Summary {
+    puzzles: usize,
+    solved: usize,
+    unsolvable: usize,
+    invalid: usize,
+    time_ms: f64,
+}
+
+#[derive(Serialize)]
+struct SummaryOutput<'a> {
+    summary: &'a Summary,
+}
+
+pub fn run(args: &BatchArgs) -> Result<(), String> {
+    if args.format == Format::Json {
+        return Err("batch writes text or ndjson".to_string());
+    }
+
+    let input: Box<dyn BufRead> = match &args.file {
+        Some(path) => {
+            let file = File::open(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+            Box::new(BufReader::new(file))
+        }
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let text = args.format == Format::Text;
+    if text {
+        println!(
+            "{:>5}  {:<18} {:>9} {:>10}  First solution",
+            "Line", "Board", "Solutions", "Time (ms)"
+        );
+    }
+
+    let mut summary = Summary::default();
+    for (number, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| format!("Could not read line {}: {}", number + 1, e))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match solve_line(number + 1, line) {
+            Ok(solved) => {
+                summary.add(&solved);
+                if text {
+                    print_row(&solved);
+                } else {
+                    print_json(args.format, &solved);
+                }
+            }
+            Err(invalid) => {
+                summary.puzzles += 1;
+                summary.invalid += 1;
+                if text {
+                    println!(
+                        "{:>5}  {:<18} {}",
+                        invalid.line, invalid.input, invalid.error
+                    );
+                } else {
+                    print_json(args.format, &invalid);
+                }
+            }
+        }
+    }
+
+    if text {
+        println!(
+            "\n{} puzzles: {} solved, {} unsolvable, {} invalid, {:.2} ms solving",
+            summary.puzzles, summary.solved, summary.unsolvable, summary.invalid, summary.time_ms
+        );
+    } else {
+        print_json(args.format, &SummaryOutput { summary: &summary });
+    }
+
+    if summary.invalid > 0 {
+        return Err(format!(
+            "{} of {} puzzles could not be read",
+            summary.invalid, summary.puzzles
+        ));
+    }
+    Ok(())
+}
+
+fn solve_line(line: usize, input: &str) -> Result<Solved, Invalid> {
+    let board = parse_puzzle(input).map_err(|e| Invalid {
+        line,
+        input: input.to_string(),
+        error: e.to_string(),
+    })?;
+
+    let start = Instant::now();
+    let solver = Solver::new(board.clone());
+    let first_solution = solver.solutions_up_to(1).pop();
+    let solution_count = solver.count_solutions();
+    let time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    Ok(Solved {
+        line,
+        id: board_id(&board),
+        board,
+        solution_count,
+        first_solution,
+        time_ms,
+    })
+}
+
+fn print_row(solved: &Solved) {
+    let first_solution = match &solved.first_solution {
+        Some(solution) => solution
+            .iter()
+            .map(|m| m.notation())
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => "-".to_string(),
+    };
+    println!(
+        "{:>5}  {:<18} {:>9} {:>10.2}  {}",
+        solved.line,
+        solved.board.board_string(),
+        solved.solution_count,
+        solved.time_ms,
+        first_solution
+    );
+}
+
+impl Summary {
+    fn add(&mut self, solved: &Solved) {
+        self.puzzles += 1;
+        self.time_ms += solved.time_ms;
+        if solved.first_solution.is_some() {
+            self.solved += 1;
+        } else {
+            self.unsolvable += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_lines() {
+        let mut summary = Summary::default();
+        let solved = solve_line(1, "N...P.R.K.NP....").ok().unwrap();
+        assert_eq!(3, solved.solution_count);
+        assert_eq!(5, solved.first_solution.as_ref().unwrap().len());
+        summary.add(&solved);
+
+        let first = Solver::new(solved.board.clone()).solve().remove(0);
+        assert_eq!(Some(first), solved.first_solution);
+
+        let by_id = solve_line(2, "202859896274992").ok().unwrap();
+        assert_eq!(solved.board.board_string(), by_id.board.board_string());
+        assert_eq!(solved.first_solution, by_id.first_solution);
+
+        let unsolvable = solve_line(3, "R..N.........P..").ok().unwrap();
+        assert_eq!(0, unsolvable.solution_count);
+        assert!(unsolvable.first_solution.is_none());
+        summary.add(&unsolvable);
+        assert_eq!(
+            (2, 1, 1),
+            (summary.puzzles, summary.solved, summary.unsolvable)
+        );
+
+        let invalid = solve_line(4, "N..X").err().unwrap();
+        assert_eq!(4, invalid.line);
+        assert_eq!("N..X", invalid.input);
+    }
+}
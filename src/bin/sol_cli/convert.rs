@@ -0,0 +1,62 @@
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::board::Board;
+
+use super::{
+    output::{Format, print_json},
+    parse_puzzle,
+};
+
+/// Turn a board id into a board string, or a board string into an id
+#[derive(FromArgs)]
+#[argh(subcommand, name = "convert")]
+pub struct ConvertArgs {
+    #[argh(positional)]
+    /// a board id, or a board string like N...P.R.K.NP....
+    puzzle: String,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the board: text (the default), json or ndjson. json
+    /// includes both the id and the board string
+    pub format: Format,
+}
+
+#[derive(Serialize)]
+struct ConvertOutput {
+    board: Board,
+    id: String,
+    width: usize,
+    height: usize,
+}
+
+pub fn run(args: &ConvertArgs) -> Result<(), String> {
+    let board = parse_puzzle(&args.puzzle).map_err(|e| e.to_string())?;
+    let from_id = args.puzzle.parse::<u128>().is_ok();
+    if !from_id && !board.has_unique_id() {
+        return Err(format!(
+            "The board has too many pieces for an id, at most {} fit",
+            board.max_identifiable_pieces()
+        ));
+    }
+
+    if args.format != Format::Text {
+        print_json(
+            args.format,
+            &ConvertOutput {
+                id: board.id().to_string(),
+                width: board.width(),
+                height: board.height(),
+                board,
+            },
+        );
+        return Ok(());
+    }
+
+    if from_id {
+        println!("{}", board.board_string());
+    } else {
+        println!("{}", board.id());
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::daily::{self, Date};
+
+use super::{
+    output::{Format, PuzzleOutput, print_json},
+    solve::{SolutionView, solve_puzzle},
+};
+
+/// Show the puzzle of the day, the same for everyone
+#[derive(FromArgs)]
+#[argh(subcommand, name = "daily")]
+pub struct DailyArgs {
+    #[argh(option)]
+    /// the day to show the puzzle for, as YYYY-MM-DD. defaults to today (UTC)
+    date: Option<String>,
+
+    #[argh(switch)]
+    /// print the solution
+    print: bool,
+
+    #[argh(switch)]
+    /// print every solution, see sol_cli solve --help
+    all: bool,
+
+    #[argh(option)]
+    /// print at most this many solutions, or groups of solutions with
+    /// --all
+    limit: Option<usize>,
+
+    #[argh(switch)]
+    /// print the solutions as a tree
+    tree: bool,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the puzzle: text (the default), json or ndjson
+    pub format: Format,
+}
+
+#[derive(Serialize)]
+pub struct DailyOutput {
+    date: String,
+    weekday: String,
+    seed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<f64>,
+}
+
+pub fn run(args: &DailyArgs) -> Result<(), String> {
+    let date = match &args.date {
+        Some(date) => Date::parse(date).ok_or("Dates should be written as YYYY-MM-DD")?,
+        None => today(),
+    };
+
+    let puzzle = daily::puzzle(date).map_err(|e| e.to_string())?;
+    if args.format != Format::Text {
+        let mut output = PuzzleOutput::new(puzzle.board, args.limit);
+        output.daily = Some(DailyOutput {
+            date: puzzle.date.to_string(),
+            weekday: date.weekday().to_string(),
+            seed: puzzle.seed,
+            difficulty: puzzle.difficulty.map(|d| d.rating),
+        });
+        print_json(args.format, &output);
+        return Ok(());
+    }
+
+    println!(
+        "Daily puzzle for {}, {} (seed {})",
+        puzzle.date,
+        date.weekday(),
+        puzzle.seed
+    );
+    if let Some(difficulty) = &puzzle.difficulty {
+        println!("{:>30}:{:>6.2}", "Difficulty", difficulty.rating);
+    }
+
+    puzzle.board.pretty_print();
+    if args.print {
+        solve_puzzle(puzzle.board, &args.solution_view());
+    }
+    Ok(())
+}
+
+fn today() -> Date {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    Date::from_unix_seconds(seconds)
+}
+
+impl DailyArgs {
+    fn solution_view(&self) -> SolutionView {
+        SolutionView {
+            all: self.all,
+            limit: self.limit,
+            tree: self.tree,
+        }
+    }
+}
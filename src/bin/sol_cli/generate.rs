@@ -0,0 +1,441 @@
+use std::{
+    collections::HashSet,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::{
+    board::{
+        Board,
+        piece::{PIECES, Piece},
+    },
+    difficulty,
+    generator::{
+        self, GenerateStats, Uniqueness,
+        config::{ConfigError, GeneratorConfig, MAX_WEIGHT},
+        random::SeededRandom,
+    },
+    pack::{Pack, PackEntry},
+    solver::Solver,
+};
+
+use super::{
+    output::{Format, PuzzleOutput, print_json, to_json},
+    solve::{SolutionView, solve_puzzle},
+};
+
+// How many tries `--count` gets per puzzle before giving up on finding more
+// puzzles that are different from the ones it has.
+const PACK_TRIES: u64 = 10;
+
+/// Generate a puzzle
+#[derive(FromArgs)]
+#[argh(subcommand, name = "generate")]
+pub struct GenerateArgs {
+    #[argh(option, short = 'n')]
+    /// number of pieces to place on the board. defaults to 5
+    num_pieces: Option<u32>,
+
+    #[argh(option)]
+    /// number of files on the board, between 3 and 8. defaults to 4
+    width: Option<usize>,
+
+    #[argh(option)]
+    /// number of ranks on the board, between 3 and 8. defaults to 4
+    height: Option<usize>,
+
+    #[argh(option)]
+    /// file to read the board size and pool of pieces to generate with from.
+    /// the other options change what it says
+    config: Option<String>,
+
+    #[argh(option)]
+    /// pieces the generator can pick from, eg: QRRBBNNNPP
+    pool: Option<String>,
+
+    #[argh(option)]
+    /// pieces every generated puzzle has, eg: K to always have a king
+    require: Option<String>,
+
+    #[argh(option)]
+    /// how likely a piece is to be picked compared to the others, eg: N=3.
+    /// pieces have a weight of 1 unless given
+    weight: Vec<String>,
+
+    #[argh(option)]
+    /// maximum number of solutions allowed for the puzzle. atleast 1. defaults to 5
+    solutions: Option<u32>,
+
+    #[argh(switch)]
+    /// generate the puzzle backwards from the last piece left, by taking
+    /// captures back. every puzzle generated this way can be solved
+    reverse: bool,
+
+    #[argh(switch)]
+    /// only generate puzzles with exactly one solution. overrides --solutions
+    unique: bool,
+
+    #[argh(switch)]
+    /// only generate puzzles whose solutions all make the same captures, in
+    /// any order. overrides --solutions
+    essentially_unique: bool,
+
+    #[argh(option)]
+    /// seed for the random number generator. the same seed and options always
+    /// generate the same puzzle. defaults to a seed taken from the clock
+    seed: Option<u64>,
+
+    #[argh(option)]
+    /// generate this many different puzzles and write them as a pack, easiest
+    /// first. each puzzle uses the next seed after the one before it
+    count: Option<usize>,
+
+    #[argh(option)]
+    /// file to write the pack to, see sol_cli pack --help. packs are text, so
+    /// this can't be used with --format json or ndjson. written to stdout
+    /// when not given
+    out: Option<String>,
+
+    #[argh(switch)]
+    /// leave puzzles out of the pack that are a puzzle already in it turned
+    /// around or mirrored
+    dedupe_symmetry: bool,
+
+    #[argh(switch)]
+    /// print the solution
+    print: bool,
+
+    #[argh(switch)]
+    /// print every solution, see sol_cli solve --help
+    all: bool,
+
+    #[argh(option)]
+    /// print at most this many solutions, or groups of solutions with
+    /// --all
+    limit: Option<usize>,
+
+    #[argh(switch)]
+    /// print the solutions as a tree
+    tree: bool,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the puzzle: text (the default), json or ndjson. json
+    /// includes every solution
+    pub format: Format,
+}
+
+/// Puzzles written out by `--count` with `--format json`.
+#[derive(Serialize)]
+struct PackOutput {
+    puzzles: Vec<PuzzleOutput>,
+}
+
+#[derive(Serialize)]
+pub struct GenerationOutput {
+    seed: u64,
+    attempts: u32,
+    pieces_placed: u32,
+    pieces_kept: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uniqueness: Option<Uniqueness>,
+}
+
+pub fn run(args: &GenerateArgs) -> Result<(), String> {
+    if args.count.is_some() || args.out.is_some() {
+        return generate_pack(args);
+    }
+
+    let seed = args.seed.unwrap_or_else(clock_seed);
+    let text = args.format == Format::Text;
+    let (board, generation) =
+        generate_puzzle(args, seed, text)?.ok_or("Failed to generate a puzzle, try again")?;
+    if args.format == Format::Text {
+        board.pretty_print();
+        if args.print {
+            solve_puzzle(board, &args.solution_view());
+        }
+    } else {
+        let mut output = PuzzleOutput::new(board, args.limit);
+        output.generation = Some(generation);
+        print_json(args.format, &output);
+    }
+    Ok(())
+}
+
+/// Generates `--count` different puzzles, sorted by how hard they are.
+fn generate_pack(args: &GenerateArgs) -> Result<(), String> {
+    if args.out.is_some() && args.format != Format::Text {
+        return Err(
+            "Packs are written as text, leave out --out to print the puzzles as JSON".to_string(),
+        );
+    }
+
+    let count = args.count.unwrap_or(1);
+    let seed = args.seed.unwrap_or_else(clock_seed);
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let mut puzzles = Vec::new();
+    for i in 0..count as u64 * PACK_TRIES {
+        if puzzles.len() == count {
+            break;
+        }
+
+        let Some((board, mut generation)) = generate_puzzle(args, seed.wrapping_add(i), false)?
+        else {
+            continue;
+        };
+        // Board strings, as ids stop telling apart boards with many pieces
+        let key = if args.dedupe_symmetry {
+            board.canonical().board_string()
+        } else {
+            board.board_string()
+        };
+        if !seen.insert(key) {
+            duplicates += 1;
+            continue;
+        }
+
+        if generation.difficulty.is_none() {
+            generation.difficulty = difficulty::rate(&board).map(|d| d.rating);
+        }
+        puzzles.push((board, generation));
+    }
+
+    let rating = |generation: &GenerationOutput| generation.difficulty.unwrap_or(f64::INFINITY);
+    puzzles.sort_by(|(_, a), (_, b)| rating(a).total_cmp(&rating(b)));
+
+    let generated = puzzles.len();
+    let written = if args.format == Format::Text {
+        let mut pack = Pack::new();
+        for (board, generation) in puzzles {
+            pack.entries.push(pack_entry(board, &generation));
+        }
+        pack.to_string()
+    } else {
+        let puzzles = puzzles
+            .into_iter()
+            .map(|(board, generation)| {
+                let mut output = PuzzleOutput::new(board, args.limit);
+                output.generation = Some(generation);
+                output
+            })
+            .collect();
+        to_json(args.format, &PackOutput { puzzles }) + "\n"
+    };
+
+    match &args.out {
+        Some(path) => {
+            fs::write(path, written).map_err(|e| format!("Could not write {}: {}", path, e))?;
+            println!(
+                "Wrote {} puzzles to {}, {} duplicates left out",
+                generated, path, duplicates
+            );
+        }
+        None => print!("{}", written),
+    }
+
+    if generated < count {
+        return Err(format!(
+            "Only {} of {} puzzles could be generated, the options may not allow more different puzzles",
+            generated, count
+        ));
+    }
+    Ok(())
+}
+
+/// A generated puzzle as written in a pack, with every one of its solutions.
+fn pack_entry(board: Board, generation: &GenerationOutput) -> PackEntry {
+    let solutions = Solver::new(board.clone()).solve();
+    let mut entry = PackEntry::new(board, solutions);
+    entry.title = format!(
+        "{} pieces, seed {}",
+        entry.board.num_pieces(),
+        generation.seed
+    );
+    entry.difficulty = generation.difficulty;
+    if let Some(uniqueness) = generation.uniqueness {
+        entry.tags.push(uniqueness.to_string().to_lowercase());
+    }
+    entry
+}
+
+/// Generates a puzzle from `seed`, printing what it does when `text` is set.
+/// `None` when the generator gave up.
+fn generate_puzzle(
+    args: &GenerateArgs,
+    seed: u64,
+    text: bool,
+) -> Result<Option<(Board, GenerationOutput)>, String> {
+    let config = generator_config(args)?;
+    let (width, height) = (config.width, config.height);
+
+    let mut num_pieces = args.num_pieces.unwrap_or(5);
+    if num_pieces < 2 {
+        num_pieces = 2;
+    }
+
+    let mut num_solutions = args.solutions.unwrap_or(5);
+    if num_solutions < 1 {
+        num_solutions = 5;
+    }
+
+    config.validate(num_pieces).map_err(|e| e.to_string())?;
+
+    let uniqueness = if args.unique {
+        Some(Uniqueness::Unique)
+    } else if args.essentially_unique {
+        Some(Uniqueness::EssentiallyUnique)
+    } else {
+        None
+    };
+
+    if uniqueness.is_some() && args.reverse {
+        return Err(
+            "Puzzles generated in reverse can't be asked for a unique solution".to_string(),
+        );
+    }
+
+    let rand = SeededRandom::new(seed);
+    let generate = if let Some(uniqueness) = uniqueness {
+        if text {
+            println!(
+                "Generating a {}x{} puzzle with {} pieces with a solution that is {}, seed {}",
+                width,
+                height,
+                num_pieces,
+                uniqueness.to_string().to_lowercase(),
+                seed
+            );
+        }
+        generator::generate_unique_with_config(&config, num_pieces, uniqueness, &rand)
+    } else if args.reverse {
+        if text {
+            println!(
+                "Generating a {}x{} puzzle in reverse with {} pieces with a maximum of {} solutions, seed {}",
+                width, height, num_pieces, num_solutions, seed
+            );
+        }
+        generator::generate_reverse_with_config(&config, num_pieces, num_solutions, &rand)
+    } else {
+        if text {
+            println!(
+                "Generating a {}x{} puzzle with {} pieces with a maximum of {} solutions, seed {}",
+                width, height, num_pieces, num_solutions, seed
+            );
+        }
+        generator::generate_with_config(&config, num_pieces, num_solutions, &rand)
+    };
+    let generator = generate.map_err(|e| e.to_string())?;
+    if text {
+        generator.print_stats();
+    }
+
+    let generation = GenerationOutput::new(seed, &generator);
+    Ok(generator.board().map(|board| (board, generation)))
+}
+
+impl GenerationOutput {
+    fn new(seed: u64, stats: &GenerateStats) -> Self {
+        GenerationOutput {
+            seed,
+            attempts: stats.attempts(),
+            pieces_placed: stats.pieces_placed(),
+            pieces_kept: stats.pieces_kept(),
+            difficulty: stats.difficulty().map(|d| d.rating),
+            uniqueness: stats.uniqueness(),
+        }
+    }
+}
+
+/// The board and pieces to generate with: the config file if there is one,
+/// changed by any of the other options.
+fn generator_config(args: &GenerateArgs) -> Result<GeneratorConfig, String> {
+    let mut config = match &args.config {
+        Some(path) => {
+            let text =
+                fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+            GeneratorConfig::parse(&text).map_err(|e| format!("{}: {}", path, e))?
+        }
+        None => GeneratorConfig::new(),
+    };
+
+    if let Some(width) = args.width {
+        config.width = width;
+    }
+    if let Some(height) = args.height {
+        config.height = height;
+    }
+
+    if let Some(pool) = &args.pool {
+        let pieces = parse_pieces(pool)
+            .ok_or("The pool should be made of the letters K, Q, R, B, N and P")?;
+        for piece in PIECES {
+            let limits = config.limits_mut(piece);
+            limits.max = pieces.iter().filter(|p| **p == piece).count() as u32;
+            limits.min = u32::min(limits.min, limits.max);
+        }
+    }
+
+    if let Some(required) = &args.require {
+        let pieces = parse_pieces(required)
+            .ok_or("Required pieces should be made of the letters K, Q, R, B, N and P")?;
+        for piece in PIECES {
+            let count = pieces.iter().filter(|p| **p == piece).count() as u32;
+            if count > 0 {
+                config.require(piece, count);
+            }
+        }
+    }
+
+    for weight in &args.weight {
+        let parsed = weight.split_once('=').and_then(|(piece, weight)| {
+            let [piece] = parse_pieces(piece)?[..] else {
+                return None;
+            };
+            Some((piece, weight.parse().ok()?))
+        });
+        let (piece, weight) = parsed.ok_or("Weights should be written like N=2")?;
+        if weight > MAX_WEIGHT {
+            let error = ConfigError::WeightTooHigh {
+                piece,
+                max: MAX_WEIGHT,
+            };
+            return Err(error.to_string());
+        }
+        config.limits_mut(piece).weight = weight;
+    }
+
+    Ok(config)
+}
+
+fn parse_pieces(pieces: &str) -> Option<Vec<Piece>> {
+    pieces
+        .chars()
+        .map(|c| match c {
+            'K' | 'Q' | 'R' | 'B' | 'N' | 'P' => Piece::parse(&c.to_string()).ok().flatten(),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+impl GenerateArgs {
+    fn solution_view(&self) -> SolutionView {
+        SolutionView {
+            all: self.all,
+            limit: self.limit,
+            tree: self.tree,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+mod batch;
+mod convert;
+mod daily;
+mod generate;
+mod output;
+mod pack;
+mod play;
+mod rate;
+mod solve;
+
+use std::{fs, path::Path, process::ExitCode};
+
+use argh::FromArgs;
+
+use sol_chess::{
+    board::{Board, errors::SError},
+    pack::Pack,
+};
+
+use crate::output::{ErrorOutput, Format, print_json};
+
+// What `sol_cli` exits with, so scripts can tell what happened.
+const FAILURE: u8 = 1;
+const USAGE: u8 = 2;
+
+/// Solitaire Chess puzzle generator and solver
+/// - v0.0.1 cool-mist
+#[derive(FromArgs)]
+#[argh(
+    error_code(1, "the command failed, like an invalid board"),
+    error_code(2, "the options couldn't be read")
+)]
+struct Args {
+    #[argh(subcommand)]
+    command: Command,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Generate(generate::GenerateArgs),
+    Solve(solve::SolveArgs),
+    Play(play::PlayArgs),
+    Convert(convert::ConvertArgs),
+    Rate(rate::RateArgs),
+    Batch(batch::BatchArgs),
+    Pack(pack::PackArgs),
+    Daily(daily::DailyArgs),
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(code) => return code,
+    };
+
+    let (format, result) = match &args.command {
+        Command::Generate(generate) => (generate.format, generate::run(generate)),
+        Command::Solve(solve) => (solve.format, solve::run(solve)),
+        Command::Play(play) => (Format::Text, play::run(play)),
+        Command::Convert(convert) => (convert.format, convert::run(convert)),
+        Command::Rate(rate) => (rate.format, rate::run(rate)),
+        Command::Batch(batch) => (batch.format, batch::run(batch)),
+        Command::Pack(pack) => (pack.format, pack::run(pack)),
+        Command::Daily(daily) => (daily.format, daily::run(daily)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            match format {
+                Format::Text => eprintln!("{}", error),
+                _ => print_json(format, &ErrorOutput { error }),
+            }
+            ExitCode::from(FAILURE)
+        }
+    }
+}
+
+/// Same as `argh::from_env`, but exits with `USAGE` when the options can't be
+/// read, instead of the code used for failed commands.
+fn parse_args() -> Result<Args, ExitCode> {
+    let strings: Vec<String> = std::env::args().collect();
+    let cmd = Path::new(&strings[0])
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&strings[0]);
+    let strs: Vec<&str> = strings.iter().map(|s| s.as_str()).collect();
+
+    Args::from_args(&[cmd], &strs[1..]).map_err(|early_exit| match early_exit.status {
+        Ok(()) => {
+            println!("{}", early_exit.output);
+            ExitCode::SUCCESS
+        }
+        Err(()) => {
+            eprintln!(
+                "{}\nRun {} --help for more information.",
+                early_exit.output, cmd
+            );
+            ExitCode::from(USAGE)
+        }
+    })
+}
+
+/// The board given with `--id` or `--board`. Exactly one of them has to be
+/// given.
+fn load_board(id: Option<u128>, board: &Option<String>) -> Result<Board, String> {
+    let board = match (id, board) {
+        (Some(id), None) => Board::from_id(id),
+        (None, Some(board)) => Board::from_string(board.clone()),
+        _ => return Err("Give the puzzle with either --id or --board".to_string()),
+    };
+    board.map_err(|e| e.to_string())
+}
+
+/// Reads and checks the puzzle pack in the file at `path`.
+fn load_pack(path: &str) -> Result<Pack, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    Pack::parse(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Reads `puzzle` as a board id if it is a number, and as a board string
+/// otherwise.
+fn parse_puzzle(puzzle: &str) -> Result<Board, SError> {
+    match puzzle.parse::<u128>() {
+        Ok(id) => Board::from_id(id),
+        Err(_) => Board::from_string(puzzle.to_string()),
+    }
+}
@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use sol_chess::{
+    board::{Board, cmove::CMove},
+    solver::Solver,
+};
+
+use super::{daily::DailyOutput, generate::GenerationOutput};
+
+/// How `sol_cli` writes what it found.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    /// One pretty printed JSON document.
+    Json,
+    /// One JSON document per line.
+    Ndjson,
+}
+
+/// A puzzle as written out by `--format json`.
+#[derive(Serialize)]
+pub struct PuzzleOutput {
+    pub board: Board,
+    // A string, as ids don't fit in the numbers most JSON readers use
+    pub id: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    pub pieces: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<GenerationOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily: Option<DailyOutput>,
+    pub solution_count: u64,
+    pub solutions: Vec<Vec<CMove>>,
+}
+
+#[derive(Serialize)]
+pub struct ErrorOutput {
+    pub error: String,
+}
+
+impl PuzzleOutput {
+    /// `limit` caps the number of solutions written out, `solution_count`
+    /// always counts all of them.
+    pub fn new(board: Board, limit: Option<usize>) -> Self {
+        let solver = Solver::new(board.clone());
+        let solutions = solver.solutions_up_to(limit.unwrap_or(usize::MAX));
+        PuzzleOutput {
+            id: board_id(&board),
+            width: board.width(),
+            height: board.height(),
+            pieces: board.num_pieces(),
+            board,
+            generation: None,
+            daily: None,
+            solution_count: solver.count_solutions(),
+            solutions,
+        }
+    }
+}
+
+/// The board id as a string, or `None` when the board has too many pieces
+/// for its id to tell it apart from other boards.
+pub fn board_id(board: &Board) -> Option<String> {
+    board.has_unique_id().then(|| board.id().to_string())
+}
+
+pub fn print_json(format: Format, output: &impl Serialize) {
+    println!("{}", to_json(format, output));
+}
+
+/// `output` as one line for ndjson, pretty printed otherwise.
+pub fn to_json(format: Format, output: &impl Serialize) -> String {
+    let json = if format == Format::Ndjson {
+        serde_json::to_string(output)
+    } else {
+        serde_json::to_string_pretty(output)
+    };
+    json.expect("Output should always serialize")
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            _ => Err("expected text, json or ndjson".to_string()),
+        }
+    }
+}
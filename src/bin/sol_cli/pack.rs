@@ -0,0 +1,108 @@
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::{
+    board::{Board, cmove::CMove},
+    pack::PACK_VERSION,
+};
+
+use super::{
+    load_pack,
+    output::{Format, board_id, print_json},
+};
+
+/// Check a puzzle pack and list its puzzles
+#[derive(FromArgs)]
+#[argh(
+    subcommand,
+    name = "pack",
+    note = "Puzzle packs are text files that start with 'sol_chess pack 1', followed by a
+'puzzle' line for every puzzle and its fields, one per line. Every puzzle
+needs a board and a solution in long notation:
+
+    sol_chess pack 1
+
+    puzzle
+    board N...P.R.K.NP....
+    title Rook tour
+    author cool-mist
+    difficulty 15.38
+    tags rooks, long
+    solution Rc3xNc2 Rc2xPd2 Rd2xKa2 Ra2xPa3 Ra3xNa4"
+)]
+pub struct PackArgs {
+    #[argh(positional)]
+    /// the pack file to read
+    file: String,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the puzzles: text (the default), json or ndjson
+    pub format: Format,
+}
+
+#[derive(Serialize)]
+struct PackOutput {
+    version: u32,
+    puzzles: Vec<EntryOutput>,
+}
+
+#[derive(Serialize)]
+struct EntryOutput {
+    board: Board,
+    id: Option<String>,
+    title: String,
+    author: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<f64>,
+    tags: Vec<String>,
+    solutions: Vec<Vec<CMove>>,
+}
+
+pub fn run(args: &PackArgs) -> Result<(), String> {
+    let pack = load_pack(&args.file)?;
+    if args.format != Format::Text {
+        let puzzles = pack
+            .entries
+            .into_iter()
+            .map(|entry| EntryOutput {
+                id: board_id(&entry.board),
+                board: entry.board,
+                title: entry.title,
+                author: entry.author,
+                difficulty: entry.difficulty,
+                tags: entry.tags,
+                solutions: entry.solutions,
+            })
+            .collect();
+        print_json(
+            args.format,
+            &PackOutput {
+                version: PACK_VERSION,
+                puzzles,
+            },
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:>4}  {:<24} {:<14} {:>10}  {:<18} Tags",
+        "", "Title", "Author", "Difficulty", "Board"
+    );
+    for (i, entry) in pack.entries.iter().enumerate() {
+        let difficulty = match entry.difficulty {
+            Some(difficulty) => format!("{:.2}", difficulty),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:>4}  {:<24} {:<14} {:>10}  {:<18} {}",
+            i + 1,
+            entry.title,
+            entry.author,
+            difficulty,
+            entry.board.board_string(),
+            entry.tags.join(", ")
+        );
+    }
+    println!("\n{} puzzles, every solution checked", pack.entries.len());
+    Ok(())
+}
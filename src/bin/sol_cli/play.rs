@@ -0,0 +1,364 @@
+use std::io::{self, BufRead, Write};
+
+use argh::FromArgs;
+
+use sol_chess::{
+    board::{Board, BoardState, cmove::CMove, piece::Piece, square::Square},
+    generator::{self, random::SeededRandom},
+    solver::Solver,
+};
+
+use super::{generate::clock_seed, load_board, load_pack};
+
+const HELP: &str = "Capture a piece every move until one is left. Write captures like RxNc2,
+Rc3xNc2 or c3c2, or one of:
+    moves   list the captures that can be made
+    hint    show a capture that still wins
+    undo    take back the last capture
+    reset   start the puzzle again
+    quit    stop playing";
+
+/// Play a puzzle in the terminal, one capture at a time
+#[derive(FromArgs)]
+#[argh(subcommand, name = "play")]
+pub struct PlayArgs {
+    #[argh(option)]
+    /// the id of the board to play
+    id: Option<u128>,
+
+    #[argh(option)]
+    /// the board to play in board representation, see sol_cli solve --help
+    board: Option<String>,
+
+    #[argh(switch)]
+    /// play a newly generated puzzle
+    generate: bool,
+
+    #[argh(option)]
+    /// play a puzzle from this puzzle pack, see sol_cli pack --help
+    pack: Option<String>,
+
+    #[argh(option, default = "1")]
+    /// the puzzle of the pack to play, counted from 1. defaults to the first
+    entry: usize,
+
+    #[argh(option, short = 'n')]
+    /// number of pieces on the generated puzzle. defaults to 5
+    num_pieces: Option<u32>,
+
+    #[argh(option)]
+    /// seed to generate the puzzle with, see sol_cli generate --help
+    seed: Option<u64>,
+}
+
+pub fn run(args: &PlayArgs) -> Result<(), String> {
+    let sources = [
+        args.id.is_some(),
+        args.board.is_some(),
+        args.generate,
+        args.pack.is_some(),
+    ];
+    if sources.iter().filter(|given| **given).count() > 1 {
+        return Err(
+            "Give the puzzle with only one of --id, --board, --generate or --pack".to_string(),
+        );
+    }
+
+    let board = if args.generate {
+        generate_puzzle(args)?
+    } else if let Some(path) = &args.pack {
+        pack_puzzle(path, args.entry)?
+    } else {
+        load_board(args.id, &args.board)?
+    };
+
+    if board.game_state == BoardState::NotStarted {
+        return Err("There are no pieces on the board".to_string());
+    }
+
+    play(board, io::stdin().lock(), &mut io::stdout()).map_err(|e| e.to_string())
+}
+
+fn generate_puzzle(args: &PlayArgs) -> Result<Board, String> {
+    let seed = args.seed.unwrap_or_else(clock_seed);
+    let num_pieces = u32::max(args.num_pieces.unwrap_or(5), 2);
+    let generated =
+        generator::generate(num_pieces, 5, &SeededRandom::new(seed)).map_err(|e| e.to_string())?;
+    println!(
+        "Generated a puzzle with {} pieces, seed {}",
+        num_pieces, seed
+    );
+    generated
+        .board()
+        .ok_or_else(|| "Failed to generate a puzzle, try again".to_string())
+}
+
+fn pack_puzzle(path: &str, number: usize) -> Result<Board, String> {
+    let pack = load_pack(path)?;
+    let entry = number
+        .checked_sub(1)
+        .and_then(|index| pack.entries.get(index))
+        .ok_or_else(|| {
+            let count = pack.entries.len();
+            format!(
+                "There is no puzzle {}, {} has {} puzzles",
+                number, path, count
+            )
+        })?;
+
+    match (entry.title.is_empty(), entry.author.is_empty()) {
+        (false, false) => println!("{}, by {}", entry.title, entry.author),
+        (false, true) => println!("{}", entry.title),
+        (true, false) => println!("By {}", entry.author),
+        (true, true) => {}
+    }
+    Ok(entry.board.clone())
+}
+
+/// Plays `start` with the commands read from `input`, until the puzzle is
+/// won or `input` runs out.
+fn play(start: Board, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+    let mut board = start.clone();
+    writeln!(output, "{}\n", HELP)?;
+    writeln!(output, "{}", board.print(true))?;
+
+    let mut lines = input.lines();
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(output)?;
+            return Ok(());
+        };
+
+        match line?.trim() {
+            "" => {}
+            "quit" => return Ok(()),
+            "help" => writeln!(output, "{}", HELP)?,
+            "moves" => {
+                let mut moves: Vec<String> = board
+                    .legal_moves
+                    .iter()
+                    .map(|m| written(&board, m))
+                    .collect();
+                moves.sort();
+                writeln!(output, "{}", moves.join(", "))?;
+            }
+            "hint" => match hint(&board) {
+                Some(mv) => writeln!(output, "Try {}", written(&board, &mv))?,
+                None => writeln!(output, "There is no way to win from here, try undo")?,
+            },
+            "undo" => match board.unmake_move() {
+                Some(mv) => {
+                    writeln!(output, "Took back {}", mv.notation())?;
+                    writeln!(output, "{}", board.print(true))?;
+                }
+                None => writeln!(output, "There is nothing to take back")?,
+            },
+            "reset" => {
+                board = start.clone();
+                writeln!(output, "{}", board.print(true))?;
+            }
+            notation => {
+                let played = parse_move(&board, notation)
+                    .and_then(|mv| board.make_move(mv).map_err(|e| e.to_string()));
+                if let Err(e) = played {
+                    writeln!(output, "{}", e)?;
+                    continue;
+                }
+
+                writeln!(output, "{}", board.print(true))?;
+                match board.game_state {
+                    BoardState::Won => {
+                        writeln!(output, "Won!")?;
+                        return Ok(());
+                    }
+                    BoardState::Lost => writeln!(
+                        output,
+                        "Lost, there are no captures left. Type undo or reset to try again"
+                    )?,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// The first capture of a solution from the current position.
+fn hint(board: &Board) -> Option<CMove> {
+    let solutions = Solver::new(board.clone()).solutions_up_to(1);
+    solutions.first()?.first().cloned()
+}
+
+/// `mv` in notation, or in long notation when another legal move has the
+/// same notation.
+fn written(board: &Board, mv: &CMove) -> String {
+    let notation = mv.notation();
+    let ambiguous = board
+        .legal_moves
+        .iter()
+        .any(|m| m != mv && m.notation() == notation);
+    if ambiguous {
+        mv.long_notation()
+    } else {
+        notation
+    }
+}
+
+/// Reads a capture written like `RxNc2`, `Rc3xNc2` or `c3c2`. Whether the
+/// capture can be made is left to `Board::make_move`.
+fn parse_move(board: &Board, notation: &str) -> Result<CMove, String> {
+    let Some((from, to)) = notation.split_once('x') else {
+        return parse_squares(board, notation);
+    };
+
+    let height = board.height();
+    let to = Square::parse_with_height(to, height).map_err(|e| e.to_string())?;
+    if let Ok(from) = Square::parse_with_height(from, height) {
+        return CMove::new(from, to).map_err(|e| e.to_string());
+    }
+
+    // Short notation only names the piece, or the file for pawns
+    let candidates: Vec<Square> = match from {
+        "a" | "b" | "c" | "d" | "e" | "f" | "g" | "h" => {
+            let file = (from.as_bytes()[0] - b'a') as usize;
+            pieces(board, |square| {
+                square.file == file && square.piece == Some(Piece::Pawn)
+            })
+        }
+        _ => match Piece::parse(from) {
+            Ok(Some(piece)) if piece != Piece::Pawn => {
+                pieces(board, |square| square.piece == Some(piece))
+            }
+            _ => return Err(format!("'{}' should be written like RxNc2", notation)),
+        },
+    };
+
+    let mut moves = Vec::new();
+    for from in candidates {
+        moves.push(CMove::new(from, to.clone()).map_err(|e| e.to_string())?);
+    }
+    let legal: Vec<&CMove> = moves
+        .iter()
+        .filter(|m| board.legal_moves.contains(m))
+        .collect();
+    match (&legal[..], moves.first()) {
+        ([mv], _) => Ok((*mv).clone()),
+        ([], Some(mv)) => Ok(mv.clone()),
+        ([], None) => Err(format!(
+            "There is nothing on the board to play {} with",
+            notation
+        )),
+        _ => Err(format!(
+            "More than one piece can play {}, write it like {}",
+            notation,
+            legal[0].long_notation()
+        )),
+    }
+}
+
+/// Reads a capture written as the two squares, like `c3c2`.
+fn parse_squares(board: &Board, notation: &str) -> Result<CMove, String> {
+    if notation.len() != 4 || !notation.is_ascii() {
+        return Err(format!(
+            "'{}' should be written like RxNc2, Rc3xNc2 or c3c2, type help for more",
+            notation
+        ));
+    }
+
+    let (from, to) = notation.split_at(2);
+    let from = square_on(board, from)?;
+    let to = square_on(board, to)?;
+    CMove::new(from, to).map_err(|e| e.to_string())
+}
+
+/// The square written like `c3`, with the piece that is on it.
+fn square_on(board: &Board, notation: &str) -> Result<Square, String> {
+    let square = Square::parse_with_height(&format!(".{}", notation), board.height())
+        .map_err(|_| format!("'{}' is not a square on the board", notation))?;
+    if square.file >= board.width() {
+        return Err(format!("'{}' is not a square on the board", notation));
+    }
+
+    let piece = board.cells[square.file][square.rank];
+    Ok(board.square(square.file, square.rank, piece))
+}
+
+fn pieces(board: &Board, matches: impl Fn(&Square) -> bool) -> Vec<Square> {
+    let mut squares = Vec::new();
+    for file in 0..board.width() {
+        for rank in 0..board.height() {
+            let square = board.square(file, rank, board.cells[file][rank]);
+            if matches(&square) {
+                squares.push(square);
+            }
+        }
+    }
+    squares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_lines(board: &str, input: &str) -> String {
+        let board = Board::from_string(board.to_string()).unwrap();
+        let mut output = Vec::new();
+        play(board, input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn play_to_a_win() {
+        let output = play_lines("N...P.R.K.NP....", "RxNc2\nc2d2\nRd2xKa2\nRxPa3\nRxNa4\n");
+        assert!(output.ends_with("Won!\n"), "{}", output);
+    }
+
+    #[test]
+    fn play_rejects_moves() {
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        assert_eq!(
+            Err("There is nothing on the board to play QxNc2 with".to_string()),
+            parse_move(&board, "QxNc2")
+        );
+        assert!(parse_move(&board, "b5c2").is_err());
+        assert!(parse_move(&board, "hello").is_err());
+
+        let output = play_lines("N...P.R.K.NP....", "RxNa4\nb2c2\nundo\n");
+        assert!(
+            output.contains("Rc3 can't capture Na4, it doesn't move that way"),
+            "{}",
+            output
+        );
+        assert!(output.contains("There is no piece on b2"), "{}", output);
+        assert!(
+            output.contains("There is nothing to take back"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn play_commands() {
+        let output = play_lines("N...P.R.K.NP....", "moves\nhint\nRxNc2\nundo\nreset\n");
+        assert!(
+            output.contains("KxPa3, NxPa3, NxRc3, RxNc2, RxPa3, dxRc3"),
+            "{}",
+            output
+        );
+        assert!(output.contains("Try "), "{}", output);
+        assert!(output.contains("Took back RxNc2"), "{}", output);
+
+        let output = play_lines("R..N.........P..", "RxNd4\nhint\n");
+        assert!(
+            output.contains("Lost, there are no captures left"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("There is no way to win from here"),
+            "{}",
+            output
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use argh::FromArgs;
+use serde::Serialize;
+
+use sol_chess::{
+    board::Board,
+    difficulty::{self, Difficulty},
+};
+
+use super::{
+    load_board,
+    output::{Format, board_id, print_json},
+};
+
+/// Rate how hard a puzzle is to solve
+#[derive(FromArgs)]
+#[argh(subcommand, name = "rate")]
+pub struct RateArgs {
+    #[argh(option)]
+    /// the id of the board to rate
+    id: Option<u128>,
+
+    #[argh(option)]
+    /// the board to rate in board representation, see sol_cli solve --help
+    board: Option<String>,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the rating: text (the default), json or ndjson
+    pub format: Format,
+}
+
+#[derive(Serialize)]
+struct RateOutput {
+    board: Board,
+    id: Option<String>,
+    difficulty: Difficulty,
+}
+
+pub fn run(args: &RateArgs) -> Result<(), String> {
+    let board = load_board(args.id, &args.board)?;
+    let Some(difficulty) = difficulty::rate(&board) else {
+        return Err("The puzzle can't be solved, so it has no rating".to_string());
+    };
+
+    if args.format != Format::Text {
+        print_json(
+            args.format,
+            &RateOutput {
+                id: board_id(&board),
+                board,
+                difficulty,
+            },
+        );
+        return Ok(());
+    }
+
+    board.pretty_print();
+    print!("{}", difficulty);
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use argh::FromArgs;
+
+use sol_chess::{
+    board::{Board, cmove::CMove},
+    solver::{self, Solver},
+};
+
+use super::{
+    load_board,
+    output::{Format, PuzzleOutput, print_json},
+};
+
+/// Solve a puzzle given by id or board string
+#[derive(FromArgs)]
+#[argh(subcommand, name = "solve")]
+pub struct SolveArgs {
+    #[argh(option)]
+    /// the id of the board to solve
+    id: Option<u128>,
+
+    #[argh(option)]
+    /// the board to solve in board representation. ranks can be separated by
+    /// '/', which is required for boards that are not square
+    board: Option<String>,
+
+    #[argh(switch)]
+    /// print every solution instead of only the first. solutions that make
+    /// the same captures in a different order are shown together
+    all: bool,
+
+    #[argh(option)]
+    /// print at most this many solutions, or groups of solutions with
+    /// --all
+    limit: Option<usize>,
+
+    #[argh(switch)]
+    /// print the solutions as a tree, with moves that start more than one
+    /// solution printed once
+    tree: bool,
+
+    #[argh(option, default = "Format::Text")]
+    /// how to write the puzzle: text (the default), json or ndjson. json
+    /// includes every solution
+    pub format: Format,
+}
+
+/// Which solutions of a puzzle to show, and how.
+pub struct SolutionView {
+    pub all: bool,
+    pub limit: Option<usize>,
+    pub tree: bool,
+}
+
+pub fn run(args: &SolveArgs) -> Result<(), String> {
+    let board = load_board(args.id, &args.board)?;
+    if args.format == Format::Text {
+        board.pretty_print();
+        solve_puzzle(board, &args.solution_view());
+    } else {
+        print_json(args.format, &PuzzleOutput::new(board, args.limit));
+    }
+    Ok(())
+}
+
+pub fn solve_puzzle(board: Board, view: &SolutionView) {
+    let solver = Solver::new(board);
+    let limit = view.limit.unwrap_or(usize::MAX);
+    let solutions = if view.all && !view.tree {
+        solver.solve()
+    } else {
+        solver.solutions_up_to(limit)
+    };
+    if solutions.is_empty() {
+        println!("No solutions found");
+        return;
+    }
+
+    let total = solver.count_solutions();
+    if view.tree {
+        print_found(total, solutions.len());
+        print_tree(&solutions.iter().map(|s| &s[..]).collect::<Vec<_>>(), "");
+    } else if view.all {
+        // The limit counts groups, so every solution is needed to group them
+        let mut groups = solver::group_equivalent(solutions);
+        groups.truncate(limit);
+        print_found(total, groups.iter().map(|g| g.len()).sum());
+        for (i, group) in groups.iter().enumerate() {
+            println!();
+            print_group(i + 1, group);
+        }
+    } else if view.limit.is_some() {
+        print_found(total, solutions.len());
+        for (i, solution) in solutions.iter().enumerate() {
+            println!();
+            println!("Solution {}", i + 1);
+            print_moves(solution);
+        }
+    } else {
+        println!("Found {} solutions", total);
+        print_moves(&solutions[0]);
+    }
+}
+
+/// Prints how many solutions there are, and how many of them are shown when
+/// that is fewer.
+fn print_found(total: u64, shown: usize) {
+    if total > shown as u64 {
+        println!("Found {} solutions, showing {}", total, shown);
+    } else {
+        println!("Found {} solutions", total);
+    }
+}
+
+pub fn print_moves(solution: &[CMove]) {
+    for (i, m) in solution.iter().enumerate() {
+        println!("{}. {}", i + 1, m.notation());
+    }
+}
+
+/// Prints the first solution of a group in full, and only the order of the
+/// captures for the others.
+fn print_group(number: usize, group: &[Vec<CMove>]) {
+    match group.len() {
+        1 => println!("Solution {}", number),
+        orders => println!("Solution {}, in {} orders", number, orders),
+    }
+
+    print_moves(&group[0]);
+    for other in &group[1..] {
+        let notation: Vec<String> = other.iter().map(|m| m.notation()).collect();
+        println!("   or {}", notation.join(", "));
+    }
+}
+
+/// Prints solutions as a tree, so moves that start more than one solution
+/// are only printed once.
+fn print_tree(solutions: &[&[CMove]], indent: &str) {
+    let mut branches: Vec<(&CMove, Vec<&[CMove]>)> = Vec::new();
+    for solution in solutions {
+        let Some((first, rest)) = solution.split_first() else {
+            continue;
+        };
+        match branches.iter_mut().find(|(m, _)| *m == first) {
+            Some((_, rests)) => rests.push(rest),
+            None => branches.push((first, vec![rest])),
+        }
+    }
+
+    for (i, (m, rests)) in branches.iter().enumerate() {
+        let last = i == branches.len() - 1;
+        let (branch, next_indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        println!("{}{}{}", indent, branch, m.notation());
+        print_tree(rests, &format!("{}{}", indent, next_indent));
+    }
+}
+
+impl SolveArgs {
+    fn solution_view(&self) -> SolutionView {
+        SolutionView {
+            all: self.all,
+            limit: self.limit,
+            tree: self.tree,
+        }
+    }
+}
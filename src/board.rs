@@ -1,8 +1,12 @@
+mod bitboard;
 pub mod cmove;
-mod constants;
+pub(crate) mod constants;
 pub mod errors;
 pub mod piece;
+#[cfg(feature = "serde")]
+mod serialize;
 pub mod square;
+mod symmetry;
 
 use core::fmt;
 use std::{
@@ -11,21 +15,39 @@ use std::{
     mem,
 };
 
+use log::{debug, trace};
+
+use bitboard::{AttackTables, piece_index};
 use cmove::CMove;
-use constants::BOARD_SIZE;
-use errors::SError;
-use piece::Piece;
+use constants::{BOARD_SIZE, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use errors::{MoveError, SError};
+use piece::{PIECES, Piece};
 use square::{Square, SquarePair};
 
+// Ids of boards other than 4x4 carry the board dimensions in their top bits.
+// 4x4 ids leave these bits blank, so ids generated before boards could be
+// resized still decode to the same board.
+const ID_DIMENSIONS_SHIFT: usize = 122;
+
 #[derive(Clone)]
 pub struct Board {
-    pub cells: [[Option<Piece>; BOARD_SIZE]; BOARD_SIZE],
+    pub cells: Vec<Vec<Option<Piece>>>,
     pub legal_moves: HashSet<CMove>,
     pub game_state: BoardState,
+    width: usize,
+    height: usize,
     pieces_remaining: u8,
+    history: Vec<CMove>,
+
+    // Bitboards of all pieces, and of each kind of piece, used to find legal
+    // moves. See `bitboard` for how squares are numbered.
+    occupied: u64,
+    bitboards: [u64; 6],
+    tables: &'static AttackTables,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoardState {
     NotStarted,
     InProgress,
@@ -35,22 +57,101 @@ pub enum BoardState {
 
 impl Board {
     pub fn new() -> Self {
+        Board::empty(BOARD_SIZE, BOARD_SIZE)
+    }
+
+    /// A blank board with `width` files and `height` ranks. Both need to be
+    /// between 3 and 8.
+    pub fn with_size(width: usize, height: usize) -> Result<Self, SError> {
+        let valid = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+        if !valid.contains(&width) || !valid.contains(&height) {
+            return Err(SError::InvalidDimensions { width, height });
+        }
+
+        Ok(Board::empty(width, height))
+    }
+
+    fn empty(width: usize, height: usize) -> Self {
         Board {
-            cells: [[None; BOARD_SIZE]; BOARD_SIZE],
+            cells: vec![vec![None; height]; width],
             legal_moves: HashSet::new(),
             pieces_remaining: 0,
+            history: Vec::new(),
             game_state: BoardState::NotStarted,
+            width,
+            height,
+            occupied: 0,
+            bitboards: [0; 6],
+            tables: AttackTables::for_size(width, height),
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of pieces left on the board.
+    pub fn num_pieces(&self) -> usize {
+        self.pieces_remaining as usize
+    }
+
     pub fn from_id(board_id: u128) -> Result<Self, SError> {
+        let dimensions = board_id >> ID_DIMENSIONS_SHIFT;
+        if dimensions == 0 {
+            return Board::from_standard_id(board_id);
+        }
+
+        let width = (dimensions >> 3) as usize + 1;
+        let height = (dimensions & 0b111) as usize + 1;
+        let mut board = Board::with_size(width, height)?;
+        let num_squares = width * height;
+        let occupancy = board_id & ((1 << num_squares) - 1);
+        if occupancy.count_ones() as usize > board.max_identifiable_pieces() {
+            return Err(SError::InvalidBoardId(board_id));
+        }
+
+        let mut working = board_id >> num_squares;
+        for i in 0..width {
+            for j in 0..height {
+                if occupancy & (1 << (i * height + j)) == 0 {
+                    continue;
+                }
+
+                let piece = Board::get_piece_from_encoding((working & 0b111) as u8, board_id)?;
+                if piece.is_none() {
+                    return Err(SError::InvalidBoardId(board_id));
+                }
+
+                working >>= 3;
+                board.set(board.square(i, j, piece));
+            }
+        }
+
+        // Anything left over between the pieces and the dimensions means the
+        // id was not produced by `Board::id`
+        if board.id() != board_id {
+            return Err(SError::InvalidBoardId(board_id));
+        }
+
+        Ok(board)
+    }
+
+    fn from_standard_id(board_id: u128) -> Result<Self, SError> {
+        if board_id >> (3 * BOARD_SIZE * BOARD_SIZE) != 0 {
+            return Err(SError::InvalidBoardId(board_id));
+        }
+
         let mut board = Board::new();
         let mut working = board_id;
         for i in (0..BOARD_SIZE).rev() {
             for j in (0..BOARD_SIZE).rev() {
                 let mask = 0b111;
-                let piece = Board::get_piece_from_encoding((working & mask) as u8);
-                working = working >> 3;
+                let piece = Board::get_piece_from_encoding((working & mask) as u8, board_id);
+                working >>= 3;
                 let piece = piece?;
                 board.set(Square::new(i, j, piece));
             }
@@ -58,16 +159,45 @@ impl Board {
         Ok(board)
     }
 
+    /// Parses a board written out rank by rank from the top, with `.` for
+    /// blank squares. Square boards can be written as a single run of
+    /// characters (16 for a 4x4 board), other boards need their ranks
+    /// separated by `/`.
     pub fn from_string(board_string: String) -> Result<Self, SError> {
-        if board_string.chars().count() != 16 {
-            return Err(SError::InvalidBoard);
-        }
+        let ranks: Vec<&str> = if board_string.contains('/') {
+            board_string.split('/').collect()
+        } else {
+            let len = board_string.chars().count();
+            let size = (MIN_BOARD_SIZE..=MAX_BOARD_SIZE)
+                .find(|size| size * size == len)
+                .ok_or(SError::InvalidBoard {
+                    position: len,
+                    reason: "boards without '/' should be square, like 16 squares for 4x4",
+                })?;
+            let mut ranks = Vec::new();
+            let mut rest = board_string.as_str();
+            for _ in 0..size {
+                let split = rest.char_indices().nth(size).map_or(rest.len(), |(i, _)| i);
+                let (rank, remaining) = rest.split_at(split);
+                ranks.push(rank);
+                rest = remaining;
+            }
+            ranks
+        };
 
-        let mut board = Board::new();
-        let mut chars = board_string.chars();
-        for r in 0..BOARD_SIZE {
-            for f in 0..BOARD_SIZE {
-                let c = chars.next().unwrap();
+        let width = ranks[0].chars().count();
+        let mut board = Board::with_size(width, ranks.len())?;
+        let separator = if board_string.contains('/') { 1 } else { 0 };
+        let mut position = 0;
+        for (r, rank) in ranks.iter().enumerate() {
+            if rank.chars().count() != width {
+                return Err(SError::InvalidBoard {
+                    position,
+                    reason: "every rank should have the same number of squares",
+                });
+            }
+
+            for (f, c) in rank.chars().enumerate() {
                 let piece = match c {
                     'K' => Piece::King,
                     'Q' => Piece::Queen,
@@ -76,19 +206,32 @@ impl Board {
                     'R' => Piece::Rook,
                     'P' => Piece::Pawn,
                     '.' => continue,
-                    _ => return Err(SError::InvalidBoard),
+                    _ => {
+                        return Err(SError::InvalidBoard {
+                            position: position + f,
+                            reason: "squares should be one of K, Q, R, B, N, P or .",
+                        });
+                    }
                 };
 
-                let square = Square::new(f, r, Some(piece));
+                let square = board.square(f, r, Some(piece));
                 board.set(square);
             }
+
+            position += width + separator;
         }
         Ok(board)
     }
 
+    /// A square on this board, which is needed to get notation right on
+    /// boards that aren't 4 ranks high.
+    pub fn square(&self, file: usize, rank: usize, piece: Option<Piece>) -> Square {
+        Square::with_height(file, rank, piece, self.height)
+    }
+
     pub fn set(&mut self, square: Square) -> Option<Piece> {
         let new_is_occuppied = square.piece.is_some();
-        let existing = mem::replace(&mut self.cells[square.file][square.rank], square.piece);
+        let existing = self.put(square.file, square.rank, square.piece);
 
         // If placing a piece on a blank, increment piece count
         if existing.is_none() && new_is_occuppied {
@@ -100,64 +243,174 @@ impl Board {
             self.pieces_remaining -= 1;
         }
 
+        // Moves made before the board was edited can't be taken back
+        self.history.clear();
         self.board_state_changed();
         existing
     }
 
-    pub fn make_move(&mut self, mv: CMove) -> Option<CMove> {
+    /// Makes `mv` if it is one of `legal_moves`, or says why it isn't.
+    pub fn make_move(&mut self, mv: CMove) -> Result<CMove, MoveError> {
         if !self.legal_moves.contains(&mv) {
-            println!("Invalid move - {}", mv.notation());
-            println!("Legal moves - ");
-            for m in &self.legal_moves {
-                println!("{}", m.notation());
-            }
-            return None;
+            let e = self.illegal_move(&mv);
+            debug!("Illegal move: {}", e);
+            trace!(
+                "Legal moves: {}",
+                self.legal_moves
+                    .iter()
+                    .map(|m| m.notation())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Err(e);
         }
 
-        let from_piece = mem::replace(&mut self.cells[mv.from.file][mv.from.rank], None);
-        self.cells[mv.to.file][mv.to.rank] = from_piece;
+        self.play(mv.clone());
+        Ok(mv)
+    }
+
+    /// Makes a move taken from `legal_moves` without checking it again.
+    pub(crate) fn play(&mut self, mv: CMove) {
+        let from_piece = self.put(mv.from.file, mv.from.rank, None);
+        self.put(mv.to.file, mv.to.rank, from_piece);
 
         self.pieces_remaining -= 1;
+        self.history.push(mv);
+        self.board_state_changed();
+    }
+
+    /// Takes back the last move made, putting the captured piece back on the
+    /// board. Returns the move that was taken back, if there was one.
+    pub fn unmake_move(&mut self) -> Option<CMove> {
+        let mv = self.history.pop()?;
+        self.put(mv.from.file, mv.from.rank, Some(mv.from_piece));
+        self.put(mv.to.file, mv.to.rank, Some(mv.to_piece));
+
+        self.pieces_remaining += 1;
         self.board_state_changed();
         Some(mv)
     }
 
+    /// Moves made on this board since a piece was last set, oldest first.
+    pub fn history(&self) -> &[CMove] {
+        &self.history
+    }
+
     pub fn empty_squares(&self) -> Vec<Square> {
         let mut empty_squares = Vec::new();
-        for file in 0..BOARD_SIZE {
-            for rank in 0..BOARD_SIZE {
+        for file in 0..self.width {
+            for rank in 0..self.height {
                 if self.cells[file][rank].is_none() {
-                    empty_squares.push(Square::new(file, rank, None));
+                    empty_squares.push(self.square(file, rank, None));
                 }
             }
         }
         empty_squares
     }
 
+    /// The empty squares `piece` could capture the piece on `target` from,
+    /// in the same order as `empty_squares`.
+    pub(crate) fn capture_origins(&self, piece: Piece, target: &Square) -> Vec<Square> {
+        let target = 1 << self.tables.square(target.file, target.rank);
+        let num_squares = self.width * self.height;
+        let mut empty = !self.occupied & (u64::MAX >> (64 - num_squares));
+        let mut origins = Vec::new();
+        while empty != 0 {
+            let from = empty.trailing_zeros() as usize;
+            empty &= empty - 1;
+            if self.tables.captures(piece, from, self.occupied) & target != 0 {
+                origins.push(self.square_at(from));
+            }
+        }
+        origins
+    }
+
+    /// The board as `Board::from_string` reads it back, with ranks separated
+    /// by `/` unless the board is square.
+    pub fn board_string(&self) -> String {
+        let separator = if self.width == self.height { "" } else { "/" };
+        self.print(false)
+            .lines()
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
     pub fn pretty_print(&self) {
         println!("{}", self.print(true));
-        println!("{:^40}\n", format!("id: {}", self.id()));
+        if self.has_unique_id() {
+            println!("{:^40}\n", format!("id: {}", self.id()));
+        }
     }
 
+    /// A number that identifies this board, see `Board::from_id`.
+    ///
+    /// 4x4 boards always get a unique id. Other boards store one bit per
+    /// square plus three bits per piece, so only their first
+    /// `max_identifiable_pieces` pieces count towards the id.
     pub fn id(&self) -> u128 {
+        if self.width == BOARD_SIZE && self.height == BOARD_SIZE {
+            return self.standard_id();
+        }
+
+        let max_pieces = self.max_identifiable_pieces();
+        let num_squares = self.width * self.height;
+        let mut occupancy: u128 = 0;
+        let mut pieces: u128 = 0;
+        let mut num_pieces = 0;
+        for i in 0..self.width {
+            for j in 0..self.height {
+                if self.cells[i][j].is_none() || num_pieces == max_pieces {
+                    continue;
+                }
+
+                let byte = Board::get_piece_encoding(self.cells[i][j]);
+                occupancy |= 1 << (i * self.height + j);
+                pieces |= (byte as u128) << (3 * num_pieces);
+                num_pieces += 1;
+            }
+        }
+
+        let dimensions = (((self.width - 1) << 3) | (self.height - 1)) as u128;
+        (dimensions << ID_DIMENSIONS_SHIFT) | (pieces << num_squares) | occupancy
+    }
+
+    /// The number of pieces this board can hold while still having a unique
+    /// `Board::id`.
+    pub fn max_identifiable_pieces(&self) -> usize {
+        let num_squares = self.width * self.height;
+        if self.width == BOARD_SIZE && self.height == BOARD_SIZE {
+            return num_squares;
+        }
+
+        usize::min(num_squares, (ID_DIMENSIONS_SHIFT - num_squares) / 3)
+    }
+
+    /// Whether `Board::id` tells this board apart from every other board.
+    pub fn has_unique_id(&self) -> bool {
+        self.pieces_remaining as usize <= self.max_identifiable_pieces()
+    }
+
+    fn standard_id(&self) -> u128 {
         let mut res: u128 = 0;
 
         for i in 0..BOARD_SIZE {
             for j in 0..BOARD_SIZE {
-                res = res << 3;
+                res <<= 3;
                 let byte = Board::get_piece_encoding(self.cells[i][j]);
-                res = res | byte as u128
+                res |= byte as u128
             }
         }
 
         res
     }
 
-    fn print(&self, pretty: bool) -> String {
+    /// The board one rank per line, with pieces as letters, or as chess
+    /// symbols centred in 40 columns when `pretty` is set.
+    pub fn print(&self, pretty: bool) -> String {
         let mut board_string = String::new();
-        for rank in 0..BOARD_SIZE {
+        for rank in 0..self.height {
             let mut row = String::new();
-            for file in 0..BOARD_SIZE {
+            for file in 0..self.width {
                 let piece = self.cells[file][rank];
                 row.push_str(&get_square_for_display(&piece, pretty));
             }
@@ -175,90 +428,134 @@ impl Board {
     }
 
     fn calc_legal_moves(&mut self) {
-        self.legal_moves = self
-            .all_possible_move_pairs()
-            .into_iter()
-            .filter(SquarePair::is_different)
-            .filter_map(|pair| self.is_legal_move(pair))
-            .collect()
-    }
-
-    fn is_legal_move(&self, pair: SquarePair) -> Option<CMove> {
-        // The below block is just to make the compiler happy. Start will always
-        // have a piece
-        let Some(piece) = pair.start.piece else {
-            return None;
-        };
-
-        let legal = match piece {
-            Piece::King => self.is_king_legal(&pair),
-            Piece::Queen => self.is_queen_legal(&pair),
-            Piece::Bishop => self.is_bishop_legal(&pair),
-            Piece::Knight => self.is_knight_legal(&pair),
-            Piece::Rook => self.is_rook_legal(&pair),
-            Piece::Pawn => self.is_pawn_legal(&pair),
-        };
-
-        if legal {
-            return Some(CMove::new(pair.start, pair.end));
+        let mut legal_moves = HashSet::new();
+        for piece in PIECES {
+            let mut from_squares = self.bitboards[piece_index(piece)];
+            while from_squares != 0 {
+                let from = from_squares.trailing_zeros() as usize;
+                from_squares &= from_squares - 1;
+
+                let mut to_squares = self.tables.captures(piece, from, self.occupied);
+                while to_squares != 0 {
+                    let to = to_squares.trailing_zeros() as usize;
+                    to_squares &= to_squares - 1;
+                    let to = self.square_at(to);
+                    if let Some(to_piece) = to.piece {
+                        legal_moves.insert(CMove::capture(
+                            piece,
+                            self.square_at(from),
+                            to_piece,
+                            to,
+                        ));
+                    }
+                }
+            }
         }
 
-        None
-    }
-
-    fn is_king_legal(&self, pair: &SquarePair) -> bool {
-        pair.dx <= 1 && pair.dy <= 1
+        self.legal_moves = legal_moves;
     }
 
-    fn is_queen_legal(&self, pair: &SquarePair) -> bool {
-        self.is_path_free(pair)
-    }
+    /// Why `mv` is not one of `legal_moves`.
+    fn illegal_move(&self, mv: &CMove) -> MoveError {
+        for square in [&mv.from, &mv.to] {
+            if square.file >= self.width || square.rank >= self.height {
+                return MoveError::OffBoard {
+                    file: square.file,
+                    rank: square.rank,
+                };
+            }
+        }
 
-    fn is_bishop_legal(&self, pair: &SquarePair) -> bool {
-        pair.dx == pair.dy && self.is_path_free(pair)
-    }
+        let from = self.square(
+            mv.from.file,
+            mv.from.rank,
+            self.cells[mv.from.file][mv.from.rank],
+        );
+        let to = self.square(mv.to.file, mv.to.rank, self.cells[mv.to.file][mv.to.rank]);
+        let Some(from_piece) = from.piece else {
+            return MoveError::BlankSource(from);
+        };
+        if from.file == to.file && from.rank == to.rank {
+            return MoveError::SameSquare(from);
+        }
+        let Some(to_piece) = to.piece else {
+            return MoveError::BlankTarget(to);
+        };
+        if from_piece != mv.from_piece {
+            return MoveError::WrongPiece {
+                square: from,
+                expected: mv.from_piece,
+            };
+        }
+        if to_piece != mv.to_piece {
+            return MoveError::WrongPiece {
+                square: to,
+                expected: mv.to_piece,
+            };
+        }
 
-    fn is_knight_legal(&self, pair: &SquarePair) -> bool {
-        (pair.dx == 2 && pair.dy == 1) || (pair.dx == 1 && pair.dy == 2)
-    }
+        let pair = SquarePair::new(from.clone(), to.clone());
+        let straight = pair.dx == 0 || pair.dy == 0;
+        let diagonal = pair.dx == pair.dy;
+        let geometry = match from_piece {
+            Piece::King => pair.dx <= 1 && pair.dy <= 1,
+            Piece::Queen => straight || diagonal,
+            Piece::Rook => straight,
+            Piece::Bishop => diagonal,
+            Piece::Knight => pair.dx * pair.dy == 2,
+            Piece::Pawn => pair.dx == 1 && pair.dy == 1 && pair.y_dir == -1,
+        };
+        if !geometry {
+            if from_piece == Piece::Pawn && pair.dx == 1 && pair.dy == 1 {
+                return MoveError::PawnDirection { from, to };
+            }
+            return MoveError::WrongGeometry { from, to };
+        }
 
-    fn is_rook_legal(&self, pair: &SquarePair) -> bool {
-        if pair.dx != 0 && pair.dy != 0 {
-            return false;
+        // Kings and pawns only move one square and knights jump, so only the
+        // sliding pieces can be blocked
+        let sliding = matches!(from_piece, Piece::Queen | Piece::Rook | Piece::Bishop);
+        let distance = if sliding {
+            usize::max(pair.dx, pair.dy)
+        } else {
+            1
+        };
+        let (mut file, mut rank) = (from.file, from.rank);
+        for _ in 1..distance {
+            file = file.wrapping_add_signed(pair.x_dir as isize);
+            rank = rank.wrapping_add_signed(pair.y_dir as isize);
+            if let Some(piece) = self.cells[file][rank] {
+                return MoveError::Blocked {
+                    by: self.square(file, rank, Some(piece)),
+                    from,
+                    to,
+                };
+            }
         }
 
-        self.is_path_free(pair)
+        MoveError::WrongGeometry { from, to }
     }
 
-    fn is_pawn_legal(&self, pair: &SquarePair) -> bool {
-        pair.dx == 1 && pair.dy == 1 && pair.y_dir == -1
+    fn square_at(&self, index: usize) -> Square {
+        let (file, rank) = self.tables.file_rank(index);
+        self.square(file, rank, self.cells[file][rank])
     }
 
-    fn is_path_free(&self, pair: &SquarePair) -> bool {
-        // There is no straight line or diagonal to get through
-        if pair.dx != pair.dy && pair.dx != 0 && pair.dy != 0 {
-            return false;
+    /// Puts `piece` on a square, keeping the bitboards in step with `cells`.
+    fn put(&mut self, file: usize, rank: usize, piece: Option<Piece>) -> Option<Piece> {
+        let index = self.tables.square(file, rank);
+        let existing = mem::replace(&mut self.cells[file][rank], piece);
+        if let Some(existing) = existing {
+            self.bitboards[piece_index(existing)] &= !(1 << index);
+            self.occupied &= !(1 << index);
         }
 
-        let x_inc = pair.x_dir;
-        let y_inc = pair.y_dir;
-        let mut x: i8 = pair.start.file.try_into().unwrap();
-        let mut y: i8 = pair.start.rank.try_into().unwrap();
-
-        loop {
-            x = x + x_inc;
-            y = y + y_inc;
-
-            let file: usize = x.try_into().unwrap();
-            let rank: usize = y.try_into().unwrap();
-            if rank == pair.end.rank && file == pair.end.file {
-                return true;
-            }
-
-            if self.cells[file][rank].is_some() {
-                return false;
-            }
+        if let Some(piece) = piece {
+            self.bitboards[piece_index(piece)] |= 1 << index;
+            self.occupied |= 1 << index;
         }
+
+        existing
     }
 
     fn calc_game_state(&mut self) {
@@ -273,37 +570,6 @@ impl Board {
         }
     }
 
-    /// This is just a cartesian product of {occupied_squares} x {occupied_squares}
-    fn all_possible_move_pairs(&self) -> impl IntoIterator<Item = SquarePair> {
-        let ret = self
-            .all_occupied_squares()
-            .into_iter()
-            .map(|start| {
-                self.all_occupied_squares()
-                    .into_iter()
-                    .map(move |end| SquarePair::new(start.clone(), end))
-            })
-            .flatten()
-            .collect::<Vec<SquarePair>>();
-
-        return ret;
-    }
-
-    fn all_occupied_squares(&self) -> impl IntoIterator<Item = Square> {
-        let mut ret = Vec::new();
-
-        for i in 0..BOARD_SIZE {
-            for j in 0..BOARD_SIZE {
-                let p = &self.cells[i][j];
-                if p.is_some() {
-                    ret.push(Square::new(i, j, *p))
-                }
-            }
-        }
-
-        ret
-    }
-
     fn board_state_changed(&mut self) {
         self.calc_legal_moves();
         self.calc_game_state();
@@ -323,7 +589,7 @@ impl Board {
         }
     }
 
-    fn get_piece_from_encoding(encoding: u8) -> Result<Option<Piece>, SError> {
+    fn get_piece_from_encoding(encoding: u8, board_id: u128) -> Result<Option<Piece>, SError> {
         match encoding {
             0b001 => Ok(Some(Piece::King)),
             0b010 => Ok(Some(Piece::Queen)),
@@ -332,11 +598,17 @@ impl Board {
             0b101 => Ok(Some(Piece::Knight)),
             0b110 => Ok(Some(Piece::Pawn)),
             0b000 => Ok(None),
-            _ => Err(SError::InvalidBoard),
+            _ => Err(SError::InvalidBoardId(board_id)),
         }
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
 fn get_square_for_display(piece: &Option<Piece>, pretty: bool) -> String {
     let contents = if let Some(piece) = piece {
         if pretty {
@@ -374,14 +646,12 @@ mod tests {
 
     macro_rules! sq {
         ($sq:literal) => {
-            Square::parse($sq)
+            Square::parse($sq).unwrap()
         };
     }
 
     macro_rules! mv {
-        ($from:literal, $to:literal) => {{
-            CMove::new(sq!($from), sq!($to))
-        }};
+        ($from:literal, $to:literal) => {{ CMove::new(sq!($from), sq!($to)).unwrap() }};
     }
 
     macro_rules! validate_board {
@@ -436,7 +706,10 @@ mod tests {
         let mut board = Board::new();
         assert_eq!(0, board.pieces_remaining);
         assert_eq!(0, board.legal_moves.len());
-        assert!(board.make_move(mv!("Rb2", "Nd1")).is_none());
+        assert_eq!(
+            Err(MoveError::BlankSource(sq!(".b2"))),
+            board.make_move(mv!("Rb2", "Nd1"))
+        );
 
         board.set(sq!("Qa4"));
         board.set(sq!("Ka2"));
@@ -486,8 +759,18 @@ mod tests {
         assert_eq!(10, board.pieces_remaining);
 
         // Validate some illegal moves
-        assert!(board.make_move(mv!("Ka2", "Pa2")).is_none());
-        assert!(board.make_move(mv!("Rb2", "Nd1")).is_none());
+        assert_eq!(
+            Err(MoveError::SameSquare(sq!("Ka2"))),
+            board.make_move(mv!("Ka2", "Pa2"))
+        );
+        assert_eq!(
+            Err(MoveError::WrongGeometry {
+                from: sq!("Rb2"),
+                to: sq!("Nd1")
+            }),
+            board.make_move(mv!("Rb2", "Nd1"))
+        );
+        assert_eq!(10, board.pieces_remaining);
 
         board.set(sq!(".b2"));
         board.set(sq!(".c4"));
@@ -517,6 +800,62 @@ mod tests {
         assert_eq!(8, board.pieces_remaining);
     }
 
+    #[test]
+    fn test_move_errors() {
+        // Q . P .
+        // . P K .
+        // K R . B
+        // P . B N
+        let mut board = Board::from_string("Q.P..PK.KR.BP.BN".to_string()).unwrap();
+        let original = board.clone();
+
+        let err = board.make_move(mv!("Qa4", "Pa1")).unwrap_err();
+        assert_eq!(
+            MoveError::Blocked {
+                from: sq!("Qa4"),
+                to: sq!("Pa1"),
+                by: sq!("Ka2")
+            },
+            err
+        );
+        assert_eq!("Qa4 can't capture Pa1, Ka2 is in the way", err.to_string());
+
+        assert_eq!(
+            Err(MoveError::PawnDirection {
+                from: sq!("Pb3"),
+                to: sq!("Ka2")
+            }),
+            board.make_move(mv!("Pb3", "Ka2"))
+        );
+        assert_eq!(
+            Err(MoveError::WrongGeometry {
+                from: sq!("Pb3"),
+                to: sq!("Kc3")
+            }),
+            board.make_move(mv!("Pb3", "Kc3"))
+        );
+        assert_eq!(
+            Err(MoveError::BlankTarget(sq!(".d3"))),
+            board.make_move(mv!("Ka2", "Pd3"))
+        );
+        assert_eq!(
+            Err(MoveError::WrongPiece {
+                square: sq!("Pa1"),
+                expected: Piece::Queen
+            }),
+            board.make_move(mv!("Ka2", "Qa1"))
+        );
+
+        let off_board = CMove::new(Square::new(4, 0, Some(Piece::Rook)), sq!("Qa4")).unwrap();
+        assert_eq!(
+            Err(MoveError::OffBoard { file: 4, rank: 0 }),
+            board.make_move(off_board)
+        );
+
+        assert_eq!(original.cells, board.cells);
+        assert!(board.history().is_empty());
+    }
+
     #[test]
     fn test_smoke_puzzle() {
         let mut board = Board::new();
@@ -537,11 +876,11 @@ mod tests {
         assert_eq!(BoardState::InProgress, board.game_state);
         assert_eq!(4, board.pieces_remaining);
 
-        assert!(board.make_move(mv!("Na1", "Rc2")).is_some());
+        assert!(board.make_move(mv!("Na1", "Rc2")).is_ok());
         assert_eq!(3, board.pieces_remaining);
         assert_eq!(BoardState::InProgress, board.game_state);
 
-        assert!(board.make_move(mv!("Pb3", "Ka4")).is_some());
+        assert!(board.make_move(mv!("Pb3", "Ka4")).is_ok());
         assert_eq!(2, board.pieces_remaining);
         assert_eq!(BoardState::Lost, board.game_state);
 
@@ -560,8 +899,8 @@ mod tests {
         assert_eq!(4, board.pieces_remaining);
         assert_eq!(BoardState::InProgress, board.game_state);
 
-        board.make_move(mv!("Qa3", "Pa4"));
-        board.make_move(mv!("Nc2", "Pa1"));
+        board.make_move(mv!("Qa3", "Pa4")).unwrap();
+        board.make_move(mv!("Nc2", "Pa1")).unwrap();
         assert_eq!(2, board.pieces_remaining);
         assert_eq!(BoardState::InProgress, board.game_state);
 
@@ -569,7 +908,7 @@ mod tests {
         // . . . .
         // . . . .
         // N . . .
-        board.make_move(mv!("Qa4", "Na1"));
+        board.make_move(mv!("Qa4", "Na1")).unwrap();
         assert_eq!(1, board.pieces_remaining);
         assert_eq!(BoardState::Won, board.game_state);
     }
@@ -590,4 +929,154 @@ mod tests {
 
         validate_board!(board2, "..NB", "....", "RQ.K", "P...");
     }
+
+    #[test]
+    fn test_sized_board() {
+        assert!(Board::with_size(2, 4).is_err());
+        assert!(Board::with_size(4, 9).is_err());
+
+        let mut board = Board::with_size(5, 5).unwrap();
+        macro_rules! sq5 {
+            ($sq:literal) => {
+                Square::parse_with_height($sq, 5).unwrap()
+            };
+        }
+
+        // . . . . Q
+        // . . . . .
+        // . . . . .
+        // . N . . .
+        // P . . . R
+        board.set(sq5!("Qe5"));
+        board.set(sq5!("Re1"));
+        board.set(sq5!("Pa1"));
+        board.set(sq5!("Nb2"));
+        assert_eq!(board.print(false), "....Q\n.....\n.....\n.N...\nP...R\n");
+
+        validate_legal_moves!(
+            board,
+            CMove::new(sq5!("Qe5"), sq5!("Re1")).unwrap(),
+            CMove::new(sq5!("Qe5"), sq5!("Nb2")).unwrap(),
+            CMove::new(sq5!("Re1"), sq5!("Qe5")).unwrap(),
+            CMove::new(sq5!("Re1"), sq5!("Pa1")).unwrap(),
+            CMove::new(sq5!("Pa1"), sq5!("Nb2")).unwrap(),
+        );
+        assert_eq!(
+            "QxRe1",
+            CMove::new(sq5!("Qe5"), sq5!("Re1")).unwrap().notation()
+        );
+
+        // Squares made without the board height still name the same squares
+        let rook_takes_queen = CMove::new(
+            Square::new(4, 4, Some(Piece::Rook)),
+            Square::new(4, 0, Some(Piece::Queen)),
+        )
+        .unwrap();
+        assert!(board.legal_moves.contains(&rook_takes_queen));
+        assert!(board.make_move(rook_takes_queen).is_ok());
+    }
+
+    #[test]
+    fn test_capture_origins() {
+        // . . N
+        // . P .
+        // . . .
+        let board = Board::from_string("..N.P....".to_string()).unwrap();
+        let knight = board.square(2, 0, Some(Piece::Knight));
+        let notation = |piece| {
+            board
+                .capture_origins(piece, &knight)
+                .iter()
+                .map(|square| square.notation())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["a3", "b3", "c2", "c1"], notation(Piece::Rook));
+        assert_eq!(vec!["a2", "b1"], notation(Piece::Knight));
+        assert!(notation(Piece::Bishop).is_empty());
+    }
+
+    #[test]
+    fn test_sized_encoding() {
+        let board = Board::from_string("..N/R.Q/P../...".to_string()).unwrap();
+        assert_eq!(3, board.width());
+        assert_eq!(4, board.height());
+        assert_eq!("..N/R.Q/P../...", board.board_string());
+
+        let board2 = Board::from_id(board.id()).unwrap();
+        assert_eq!(3, board2.width());
+        assert_eq!(4, board2.height());
+        validate_board!(board2, "..N", "R.Q", "P..", "...");
+
+        let board = Board::from_string(
+            "Q......B..N.....R..P......K..............N.......P.....P........".to_string(),
+        )
+        .unwrap();
+        assert_eq!(8, board.width());
+        let board2 = Board::from_id(board.id()).unwrap();
+        assert_eq!(board.cells, board2.cells);
+
+        // Legacy 4x4 ids keep decoding to 4x4 boards
+        let board = Board::from_id(202859896274992).unwrap();
+        assert_eq!(4, board.width());
+        assert_eq!("N...P.R.K.NP....", board.board_string());
+        assert_eq!(202859896274992, board.id());
+
+        assert_eq!(
+            Some(SError::InvalidBoard {
+                position: 8,
+                reason: "every rank should have the same number of squares",
+            }),
+            Board::from_string("..N/R.Q/P.".to_string()).err()
+        );
+        assert_eq!(
+            Some(SError::InvalidBoard {
+                position: 10,
+                reason: "squares should be one of K, Q, R, B, N, P or .",
+            }),
+            Board::from_string("..N/R.Q/P.X".to_string()).err()
+        );
+        assert_eq!(
+            Some(SError::InvalidDimensions {
+                width: 2,
+                height: 2
+            }),
+            Board::from_string("../..".to_string()).err()
+        );
+        assert_eq!(
+            Some(SError::InvalidBoardId(u128::MAX)),
+            Board::from_id(u128::MAX).err()
+        );
+    }
+
+    #[test]
+    fn test_unmake_move() {
+        let mut board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let original = board.clone();
+        assert!(board.unmake_move().is_none());
+
+        // N . . .
+        // P . R .
+        // K . N P
+        // . . . .
+        assert!(board.make_move(mv!("Rc3", "Nc2")).is_ok());
+        assert!(board.make_move(mv!("Rc2", "Pd2")).is_ok());
+        assert_eq!(2, board.history().len());
+        assert_eq!(4, board.pieces_remaining);
+
+        assert_eq!(Some(mv!("Rc2", "Pd2")), board.unmake_move());
+        assert_eq!(Some(mv!("Rc3", "Nc2")), board.unmake_move());
+        assert!(board.unmake_move().is_none());
+
+        assert_eq!(original.cells, board.cells);
+        assert_eq!(original.legal_moves, board.legal_moves);
+        assert_eq!(original.id(), board.id());
+        assert_eq!(BoardState::InProgress, board.game_state);
+        assert_eq!(6, board.pieces_remaining);
+
+        // Editing the board forgets the moves made so far
+        board.make_move(mv!("Rc3", "Nc2")).unwrap();
+        board.set(sq!(".a4"));
+        assert!(board.history().is_empty());
+        assert!(board.unmake_move().is_none());
+    }
 }
@@ -0,0 +1,250 @@
+use std::sync::OnceLock;
+
+use super::constants::{MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use super::piece::Piece;
+
+// Squares are numbered file by file: the square at (file, rank) is bit
+// `file * height + rank` of a bitboard. Boards are at most 8x8, so a u64 always
+// has room for every square.
+
+const NUM_SIZES: usize = MAX_BOARD_SIZE - MIN_BOARD_SIZE + 1;
+
+// (file, rank) steps for each sliding direction. Rook directions come first,
+// then bishop directions.
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, -1),
+    (0, 1),
+    (1, 0),
+    (-1, 0),
+    (1, -1),
+    (1, 1),
+    (-1, -1),
+    (-1, 1),
+];
+const ROOK_DIRECTIONS: [usize; 4] = [0, 1, 2, 3];
+const BISHOP_DIRECTIONS: [usize; 4] = [4, 5, 6, 7];
+
+const KING_STEPS: [(i8, i8); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+// Pawns only ever capture towards the top of the board
+const PAWN_STEPS: [(i8, i8); 2] = [(-1, -1), (1, -1)];
+
+static TABLES: [OnceLock<AttackTables>; NUM_SIZES * NUM_SIZES] =
+    [const { OnceLock::new() }; NUM_SIZES * NUM_SIZES];
+
+/// Squares attacked by each piece from each square of a board of a given
+/// size, ignoring what else is on the board.
+pub(crate) struct AttackTables {
+    height: usize,
+    king: Vec<u64>,
+    knight: Vec<u64>,
+    pawn: Vec<u64>,
+
+    // Every square in each direction of `DIRECTIONS`, not including the
+    // starting square.
+    rays: [Vec<u64>; 8],
+
+    // Whether square numbers go up when walking in each direction of
+    // `DIRECTIONS`. Decides which end of a ray is closest to the start.
+    ascending: [bool; 8],
+}
+
+impl AttackTables {
+    /// The tables for a board of this size, built the first time they are
+    /// asked for.
+    pub(crate) fn for_size(width: usize, height: usize) -> &'static AttackTables {
+        let index = (width - MIN_BOARD_SIZE) * NUM_SIZES + (height - MIN_BOARD_SIZE);
+        TABLES[index].get_or_init(|| AttackTables::new(width, height))
+    }
+
+    fn new(width: usize, height: usize) -> Self {
+        let num_squares = width * height;
+        let mut tables = AttackTables {
+            height,
+            king: vec![0; num_squares],
+            knight: vec![0; num_squares],
+            pawn: vec![0; num_squares],
+            rays: std::array::from_fn(|_| vec![0; num_squares]),
+            ascending: DIRECTIONS.map(|(df, dr)| df > 0 || (df == 0 && dr > 0)),
+        };
+
+        for file in 0..width {
+            for rank in 0..height {
+                let square = tables.square(file, rank);
+                tables.king[square] = tables.steps(width, file, rank, &KING_STEPS);
+                tables.knight[square] = tables.steps(width, file, rank, &KNIGHT_STEPS);
+                tables.pawn[square] = tables.steps(width, file, rank, &PAWN_STEPS);
+                for (direction, step) in DIRECTIONS.iter().enumerate() {
+                    let mut ray = 0;
+                    let mut next = (file, rank);
+                    while let Some((f, r)) = offset(width, height, next, *step) {
+                        ray |= 1 << tables.square(f, r);
+                        next = (f, r);
+                    }
+                    tables.rays[direction][square] = ray;
+                }
+            }
+        }
+
+        tables
+    }
+
+    pub(crate) fn square(&self, file: usize, rank: usize) -> usize {
+        file * self.height + rank
+    }
+
+    pub(crate) fn file_rank(&self, square: usize) -> (usize, usize) {
+        (square / self.height, square % self.height)
+    }
+
+    /// Squares in `occupied` that `piece` standing on `square` can capture.
+    pub(crate) fn captures(&self, piece: Piece, square: usize, occupied: u64) -> u64 {
+        let attacks = match piece {
+            Piece::King => self.king[square],
+            Piece::Knight => self.knight[square],
+            Piece::Pawn => self.pawn[square],
+            Piece::Rook => self.slide(&ROOK_DIRECTIONS, square, occupied),
+            Piece::Bishop => self.slide(&BISHOP_DIRECTIONS, square, occupied),
+            Piece::Queen => {
+                self.slide(&ROOK_DIRECTIONS, square, occupied)
+                    | self.slide(&BISHOP_DIRECTIONS, square, occupied)
+            }
+        };
+
+        attacks & occupied
+    }
+
+    /// The first occupied square along each direction.
+    fn slide(&self, directions: &[usize], square: usize, occupied: u64) -> u64 {
+        let mut attacks = 0;
+        for &direction in directions {
+            let blockers = self.rays[direction][square] & occupied;
+            if blockers == 0 {
+                continue;
+            }
+
+            let nearest = if self.ascending[direction] {
+                blockers.trailing_zeros()
+            } else {
+                63 - blockers.leading_zeros()
+            };
+            attacks |= 1 << nearest;
+        }
+
+        attacks
+    }
+
+    fn steps(&self, width: usize, file: usize, rank: usize, steps: &[(i8, i8)]) -> u64 {
+        steps
+            .iter()
+            .filter_map(|step| offset(width, self.height, (file, rank), *step))
+            .fold(0, |acc, (f, r)| acc | 1 << self.square(f, r))
+    }
+}
+
+// Where `piece` is in `PIECES`, and in the per-piece bitboards
+pub(crate) fn piece_index(piece: Piece) -> usize {
+    match piece {
+        Piece::King => 0,
+        Piece::Queen => 1,
+        Piece::Rook => 2,
+        Piece::Bishop => 3,
+        Piece::Knight => 4,
+        Piece::Pawn => 5,
+    }
+}
+
+fn offset(
+    width: usize,
+    height: usize,
+    (file, rank): (usize, usize),
+    (df, dr): (i8, i8),
+) -> Option<(usize, usize)> {
+    let file = file.checked_add_signed(df as isize)?;
+    let rank = rank.checked_add_signed(dr as isize)?;
+    if file >= width || rank >= height {
+        return None;
+    }
+
+    Some((file, rank))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attack_tables() {
+        let tables = AttackTables::for_size(3, 3);
+        let everything = (1 << 9) - 1;
+        let centre = tables.square(1, 1);
+        assert_eq!(
+            8,
+            tables
+                .captures(Piece::King, centre, everything)
+                .count_ones()
+        );
+        assert_eq!(
+            0,
+            tables
+                .captures(Piece::Knight, centre, everything)
+                .count_ones()
+        );
+        assert_eq!(
+            2,
+            tables
+                .captures(Piece::Pawn, centre, everything)
+                .count_ones()
+        );
+        assert_eq!(
+            8,
+            tables
+                .captures(Piece::Queen, centre, everything)
+                .count_ones()
+        );
+
+        // A rook in the corner only sees the nearest piece on each line
+        let corner = tables.square(0, 0);
+        let rook = tables.captures(Piece::Rook, corner, everything);
+        assert_eq!(1 << tables.square(1, 0) | 1 << tables.square(0, 1), rook);
+
+        let far = 1 << tables.square(2, 0) | 1 << tables.square(2, 2);
+        assert_eq!(
+            1 << tables.square(2, 0),
+            tables.captures(Piece::Rook, corner, far)
+        );
+        assert_eq!(
+            1 << tables.square(2, 2),
+            tables.captures(Piece::Bishop, corner, far)
+        );
+        assert_eq!(far, tables.captures(Piece::Queen, corner, far));
+
+        let tables = AttackTables::for_size(8, 8);
+        let corner = tables.square(7, 7);
+        let far = 1 << tables.square(0, 0) | 1 << tables.square(3, 3);
+        assert_eq!(
+            1 << tables.square(3, 3),
+            tables.captures(Piece::Bishop, corner, far)
+        );
+        assert_eq!((6, 5), tables.file_rank(tables.square(6, 5)));
+    }
+}
@@ -1,6 +1,6 @@
-use super::{piece::Piece, square::Square};
+use super::{errors::SError, piece::Piece, square::Square};
 
-#[derive(PartialEq, Hash, Eq, Clone)]
+#[derive(PartialEq, Hash, Eq, Clone, Debug)]
 pub struct CMove {
     pub from_piece: Piece,
     pub from: Square,
@@ -12,23 +12,36 @@ pub struct CMove {
 }
 
 impl CMove {
-    pub fn new(from: Square, to: Square) -> Self {
-        let disambig = String::from("");
-        let from_piece = from.piece.expect("Trying to move a blank");
-        let to_piece = to.piece.expect("Trying to capture a blank");
+    /// A capture of the piece on `to` by the piece on `from`. Both squares
+    /// need a piece on them.
+    pub fn new(from: Square, to: Square) -> Result<Self, SError> {
+        let from_piece = from
+            .piece
+            .ok_or_else(|| SError::BlankSquare(from.notation()))?;
+        let to_piece = to.piece.ok_or_else(|| SError::BlankSquare(to.notation()))?;
+        Ok(CMove::capture(from_piece, from, to_piece, to))
+    }
+
+    pub(crate) fn capture(from_piece: Piece, from: Square, to_piece: Piece, to: Square) -> Self {
         CMove {
             from_piece,
             from,
             to_piece,
             to,
-            disambig,
+            disambig: String::from(""),
         }
     }
 
+    /// Both squares written out in full, like `Rc3xNc2`. Unlike `notation`,
+    /// this is enough to tell the move apart without the board.
+    pub fn long_notation(&self) -> String {
+        format!("{}x{}", self.from.notation(), self.to.notation())
+    }
+
     pub fn notation(&self) -> String {
-        let piece_qualifier = match &self.from_piece {
-            Piece::Pawn => self.from.file_notation(),
-            p => p.notation(),
+        let piece_qualifier = match (&self.from_piece, self.from.file_notation()) {
+            (Piece::Pawn, Some(file)) => file,
+            (p, _) => p.notation(),
         };
         format!(
             "{}{}x{}",
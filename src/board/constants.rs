@@ -1 +1,3 @@
 pub(crate) const BOARD_SIZE: usize = 4;
+pub(crate) const MIN_BOARD_SIZE: usize = 3;
+pub(crate) const MAX_BOARD_SIZE: usize = 8;
@@ -1,4 +1,231 @@
-#[derive(Debug)]
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use super::{
+    constants::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    piece::Piece,
+    square::Square,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SError {
-    InvalidBoard,
+    /// An id that `Board::id` would never give.
+    InvalidBoardId(u128),
+
+    /// A board string that can't be read. `position` counts characters from
+    /// the start of the string.
+    InvalidBoard {
+        position: usize,
+        reason: &'static str,
+    },
+
+    /// Boards are between 3x3 and 8x8.
+    InvalidDimensions { width: usize, height: usize },
+
+    /// A letter that doesn't stand for a piece.
+    InvalidPiece(String),
+
+    /// Square notation that can't be read, like `Pz9`. `position` counts
+    /// characters from the start of the notation.
+    InvalidSquare {
+        notation: String,
+        position: usize,
+        reason: &'static str,
+    },
+
+    /// A move from or to a square without a piece on it.
+    BlankSquare(String),
+
+    /// Generator options that no puzzle can be built with.
+    InvalidConfig(ConfigError),
+
+    /// The generator gave up before finding a puzzle.
+    NoPuzzleGenerated,
+}
+
+/// Why a `GeneratorConfig` can't be used to build puzzles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Boards are between 3x3 and 8x8.
+    InvalidDimensions { width: usize, height: usize },
+
+    /// A piece has a higher `min` than `max`.
+    MinAboveMax(Piece),
+
+    /// The pieces that have to be placed don't fit in the puzzle.
+    TooManyRequired { required: u32 },
+
+    /// The pool or the board has room for fewer pieces than asked for.
+    TooManyPieces { available: u32 },
+
+    /// A piece has a weight above `max`, see `config::MAX_WEIGHT`.
+    WeightTooHigh { piece: Piece, max: u32 },
+
+    /// A line of a config file that can't be read, counted from 1.
+    InvalidLine(usize),
+}
+
+/// Why `Board::make_move` turned a move down. Squares hold the piece that is
+/// on the board, which is not always the one the move said.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    /// A square past the edge of the board. Ranks are counted from the top.
+    OffBoard { file: usize, rank: usize },
+
+    /// There is no piece to move.
+    BlankSource(Square),
+
+    /// Every move is a capture, so it has to end on a piece.
+    BlankTarget(Square),
+
+    /// The move starts and ends on the same square.
+    SameSquare(Square),
+
+    /// The board has a different piece on `square` than the move said.
+    WrongPiece { square: Square, expected: Piece },
+
+    /// Pawns only capture diagonally towards the top of the board.
+    PawnDirection { from: Square, to: Square },
+
+    /// The piece doesn't move that way, like a rook moving diagonally.
+    WrongGeometry { from: Square, to: Square },
+
+    /// A piece stands between the two squares.
+    Blocked {
+        from: Square,
+        to: Square,
+        by: Square,
+    },
+}
+
+impl Display for SError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SError::InvalidBoardId(id) => write!(f, "{} is not a board id", id),
+            SError::InvalidBoard { position, reason } => {
+                write!(f, "Invalid board at character {}: {}", position, reason)
+            }
+            SError::InvalidDimensions { width, height } => write!(
+                f,
+                "A {}x{} board is not allowed, width and height should be between 3 and 8",
+                width, height
+            ),
+            SError::InvalidPiece(piece) => write!(
+                f,
+                "Invalid piece '{}', pieces are one of K, Q, R, B, N and P, or . for blank",
+                piece
+            ),
+            SError::InvalidSquare {
+                notation,
+                position,
+                reason,
+            } => write!(
+                f,
+                "Invalid square '{}' at character {}: {}",
+                notation, position, reason
+            ),
+            SError::BlankSquare(square) => write!(f, "There is no piece on {}", square),
+            SError::InvalidConfig(e) => write!(f, "{}", e),
+            SError::NoPuzzleGenerated => write!(f, "No puzzle was generated"),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidDimensions { width, height } => write!(
+                f,
+                "A {}x{} board is not allowed, width and height should be between {} and {}",
+                width, height, MIN_BOARD_SIZE, MAX_BOARD_SIZE
+            ),
+            ConfigError::MinAboveMax(piece) => write!(
+                f,
+                "The minimum number of {} pieces is above the maximum",
+                piece.notation()
+            ),
+            ConfigError::TooManyRequired { required } => write!(
+                f,
+                "Number of pieces to place on the board should be >= {}",
+                required
+            ),
+            ConfigError::TooManyPieces { available } => write!(
+                f,
+                "Number of pieces to place on the board should be <= {}",
+                available
+            ),
+            ConfigError::WeightTooHigh { piece, max } => write!(
+                f,
+                "The weight of {} pieces should be <= {}",
+                piece.notation(),
+                max
+            ),
+            ConfigError::InvalidLine(line) => write!(f, "Invalid config on line {}", line),
+        }
+    }
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::OffBoard { .. } => write!(f, "The move goes off the board"),
+            MoveError::BlankSource(square) => {
+                write!(f, "There is no piece on {}", square.notation())
+            }
+            MoveError::BlankTarget(square) => {
+                write!(f, "There is nothing to capture on {}", square.notation())
+            }
+            MoveError::SameSquare(square) => {
+                write!(f, "{} can't capture itself", square.notation())
+            }
+            MoveError::WrongPiece { square, expected } => write!(
+                f,
+                "Expected {} but found {}",
+                Square::with_height(square.file, square.rank, Some(*expected), square.height())
+                    .notation(),
+                square.notation()
+            ),
+            MoveError::PawnDirection { from, to } => write!(
+                f,
+                "{} can't capture {}, pawns only capture diagonally forward",
+                from.notation(),
+                to.notation()
+            ),
+            MoveError::WrongGeometry { from, to } => write!(
+                f,
+                "{} can't capture {}, it doesn't move that way",
+                from.notation(),
+                to.notation()
+            ),
+            MoveError::Blocked { from, to, by } => write!(
+                f,
+                "{} can't capture {}, {} is in the way",
+                from.notation(),
+                to.notation(),
+                by.notation()
+            ),
+        }
+    }
+}
+
+impl Error for SError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SError::InvalidConfig(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Error for MoveError {}
+
+// Generator functions report config errors with the rest of their errors
+impl From<ConfigError> for SError {
+    fn from(e: ConfigError) -> Self {
+        SError::InvalidConfig(e)
+    }
 }
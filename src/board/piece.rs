@@ -1,3 +1,5 @@
+use super::errors::SError;
+
 #[derive(Clone, Eq, Hash, Copy, Debug, PartialEq)]
 pub enum Piece {
     King,
@@ -8,17 +10,28 @@ pub enum Piece {
     Pawn,
 }
 
+/// Every kind of piece, in the order bitboards keep them in.
+pub const PIECES: [Piece; 6] = [
+    Piece::King,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
 impl Piece {
-    pub fn parse(piece: &str) -> Option<Self> {
+    /// Reads a piece letter, or `.` for a blank square.
+    pub fn parse(piece: &str) -> Result<Option<Self>, SError> {
         match piece {
-            "K" => Some(Piece::King),
-            "Q" => Some(Piece::Queen),
-            "B" => Some(Piece::Bishop),
-            "N" => Some(Piece::Knight),
-            "R" => Some(Piece::Rook),
-            "P" => Some(Piece::Pawn),
-            "." => None,
-            p => panic!("Invalid piece {}", p),
+            "K" => Ok(Some(Piece::King)),
+            "Q" => Ok(Some(Piece::Queen)),
+            "B" => Ok(Some(Piece::Bishop)),
+            "N" => Ok(Some(Piece::Knight)),
+            "R" => Ok(Some(Piece::Rook)),
+            "P" => Ok(Some(Piece::Pawn)),
+            "." => Ok(None),
+            p => Err(SError::InvalidPiece(p.to_string())),
         }
     }
 
@@ -55,7 +68,7 @@ mod tests {
 
     macro_rules! p {
         ($piece:literal) => {
-            Piece::parse($piece)
+            Piece::parse($piece).unwrap()
         };
     }
 
@@ -67,5 +80,10 @@ mod tests {
         assert_eq!(p!("N"), Some(Piece::Knight));
         assert_eq!(p!("R"), Some(Piece::Rook));
         assert_eq!(p!("P"), Some(Piece::Pawn));
+        assert_eq!(p!("."), None);
+        assert_eq!(
+            Err(SError::InvalidPiece("X".to_string())),
+            Piece::parse("X")
+        );
     }
 }
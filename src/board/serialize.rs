@@ -0,0 +1,151 @@
+// Serde support for the `serde` feature. Everything is written the way people
+// write it: boards as `Board::from_string` reads them, pieces as their letter,
+// and squares and moves in notation. Notation counts ranks from the bottom, so
+// squares on boards that aren't 4 ranks tall carry the height, like `Nc3@5`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use super::{
+    Board, cmove::CMove, constants::BOARD_SIZE, errors::SError, piece::Piece, square::Square,
+};
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.board_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let board = String::deserialize(deserializer)?;
+        Board::from_string(board).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.notation())
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let piece = String::deserialize(deserializer)?;
+        match Piece::parse(&piece) {
+            Ok(Some(piece)) => Ok(piece),
+            Ok(None) => Err(de::Error::custom(SError::InvalidPiece(piece))),
+            Err(e) => Err(de::Error::custom(e)),
+        }
+    }
+}
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let blank = if self.is_occupied() { "" } else { "." };
+        let square = format!("{}{}", blank, self.notation());
+        serializer.serialize_str(&with_height(square, self.height()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let square = String::deserialize(deserializer)?;
+        let (notation, height) = split_height(&square).map_err(de::Error::custom)?;
+        Square::parse_with_height(notation, height).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for CMove {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&with_height(self.long_notation(), self.from.height()))
+    }
+}
+
+impl<'de> Deserialize<'de> for CMove {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mv = String::deserialize(deserializer)?;
+        let (notation, height) = split_height(&mv).map_err(de::Error::custom)?;
+        let (from, to) = notation
+            .split_once('x')
+            .ok_or_else(|| de::Error::custom(format!("'{}' should be written like Rc3xNc2", mv)))?;
+        let from = Square::parse_with_height(from, height).map_err(de::Error::custom)?;
+        let to = Square::parse_with_height(to, height).map_err(de::Error::custom)?;
+        CMove::new(from, to).map_err(de::Error::custom)
+    }
+}
+
+fn with_height(notation: String, height: usize) -> String {
+    if height == BOARD_SIZE {
+        notation
+    } else {
+        format!("{}@{}", notation, height)
+    }
+}
+
+fn split_height(notation: &str) -> Result<(&str, usize), String> {
+    let Some((notation, height)) = notation.split_once('@') else {
+        return Ok((notation, BOARD_SIZE));
+    };
+
+    match height.parse() {
+        Ok(height) => Ok((notation, height)),
+        Err(_) => Err(format!("'{}' is not a number of ranks", height)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::BoardState, solver::Solver};
+
+    use super::*;
+
+    #[test]
+    fn serialize_board() {
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!("\"N...P.R.K.NP....\"", json);
+        let board2: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(board.id(), board2.id());
+
+        let board = Board::from_string("..N/R.Q/P../...".to_string()).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!("\"..N/R.Q/P../...\"", json);
+        let board2: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(board.cells, board2.cells);
+
+        assert!(serde_json::from_str::<Board>("\"N..\"").is_err());
+        assert_eq!(
+            "\"InProgress\"",
+            serde_json::to_string(&BoardState::InProgress).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_moves() {
+        assert_eq!("\"N\"", serde_json::to_string(&Piece::Knight).unwrap());
+        assert_eq!(Piece::Rook, serde_json::from_str("\"R\"").unwrap());
+        assert!(serde_json::from_str::<Piece>("\".\"").is_err());
+
+        let square = Square::parse("Nc3").unwrap();
+        assert_eq!("\"Nc3\"", serde_json::to_string(&square).unwrap());
+        let blank = Square::parse_with_height(".b5", 5).unwrap();
+        let json = serde_json::to_string(&blank).unwrap();
+        assert_eq!("\".b5@5\"", json);
+        assert_eq!(blank, serde_json::from_str(&json).unwrap());
+
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let solutions = Solver::new(board).solve();
+        let json = serde_json::to_string(&solutions[0]).unwrap();
+        let moves: Vec<CMove> = serde_json::from_str(&json).unwrap();
+        assert_eq!(solutions[0], moves);
+
+        let board = Board::from_string("R../.../.../.../P..".to_string()).unwrap();
+        let mv = board.legal_moves.iter().next().unwrap().clone();
+        let json = serde_json::to_string(&mv).unwrap();
+        assert_eq!("\"Ra5xPa1@5\"", json);
+        assert_eq!(mv, serde_json::from_str(&json).unwrap());
+
+        assert!(serde_json::from_str::<CMove>("\"RxNc2\"").is_err());
+        assert!(serde_json::from_str::<CMove>("\"Rc3xNc2@x\"").is_err());
+    }
+}
@@ -1,16 +1,25 @@
 use super::constants::BOARD_SIZE;
+use super::errors::SError;
 use super::piece::Piece;
 use core::fmt;
+use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Eq, Hash, PartialEq)]
+const FILES: &str = "abcdefgh";
+
+#[derive(Clone)]
 pub struct Square {
     // a = 0, b = 1, c = 2, d = 3 and so on.
     pub file: usize,
 
-    // 1 = 0, 2 = 1, 3 = 2, 4 = 3 and so on.
+    // Counted from the top of the board. On a 4x4 board, 4 = 0, 3 = 1, 2 = 2
+    // and 1 = 3.
     pub rank: usize,
 
     pub piece: Option<Piece>,
+
+    // Number of ranks on the board this square belongs to. Needed to turn
+    // `rank` back into notation, so it is left out of comparisons.
+    height: usize,
 }
 
 pub struct SquarePair {
@@ -24,45 +33,80 @@ pub struct SquarePair {
 
 impl Square {
     pub fn new(file: usize, rank: usize, piece: Option<Piece>) -> Self {
-        Square { file, rank, piece }
+        Square::with_height(file, rank, piece, BOARD_SIZE)
     }
 
-    pub fn parse(notation: &str) -> Self {
-        let mut chars = notation.chars();
-        let piece = chars.next().expect("Piece missing");
-        let piece = Piece::parse(&piece.to_string());
-        let file = chars.next().expect("File missing");
-        let file = match file {
-            'a' => 0,
-            'b' => 1,
-            'c' => 2,
-            'd' => 3,
-            _ => panic!("file should be between a-d"),
+    pub fn with_height(file: usize, rank: usize, piece: Option<Piece>, height: usize) -> Self {
+        Square {
+            file,
+            rank,
+            piece,
+            height,
+        }
+    }
+
+    /// Reads a square on a 4x4 board written as a piece (or `.` for a
+    /// blank), file and rank, like `Kd4`.
+    pub fn parse(notation: &str) -> Result<Self, SError> {
+        Square::parse_with_height(notation, BOARD_SIZE)
+    }
+
+    /// Same as `parse`, on a board with `height` ranks.
+    pub fn parse_with_height(notation: &str, height: usize) -> Result<Self, SError> {
+        let invalid = |position, reason| SError::InvalidSquare {
+            notation: notation.to_string(),
+            position,
+            reason,
         };
 
-        let rank = chars.next().unwrap().to_digit(10).expect("rank missing") as usize;
-        if rank < 1 || rank > BOARD_SIZE {
-            panic!("rank should be between 1-{}", BOARD_SIZE);
+        let mut chars = notation.chars();
+        let piece = chars.next().ok_or_else(|| invalid(0, "piece missing"))?;
+        let piece = Piece::parse(&piece.to_string())
+            .map_err(|_| invalid(0, "piece should be one of K, Q, R, B, N, P or ."))?;
+
+        let file = chars.next().ok_or_else(|| invalid(1, "file missing"))?;
+        let file = FILES
+            .find(file)
+            .ok_or_else(|| invalid(1, "file should be between a and h"))?;
+
+        let rank = chars.next().ok_or_else(|| invalid(2, "rank missing"))?;
+        let rank = rank
+            .to_digit(10)
+            .ok_or_else(|| invalid(2, "rank should be a number"))? as usize;
+        if !(1..=height).contains(&rank) {
+            return Err(invalid(2, "rank is off the board"));
         }
-        let rank = BOARD_SIZE - rank;
-        Square::new(file, rank, piece)
+
+        if chars.next().is_some() {
+            return Err(invalid(3, "unexpected characters after the rank"));
+        }
+
+        let rank = height - rank;
+        Ok(Square::with_height(file, rank, piece, height))
     }
 
-    pub fn file_notation(&self) -> String {
-        String::from("abcd".chars().nth(self.file).unwrap())
+    /// Number of ranks on the board this square belongs to.
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    pub fn rank_notation(&self) -> String {
-        format!("{}", BOARD_SIZE - self.rank)
+    /// The file as a letter, or `None` when it is off every board.
+    pub fn file_notation(&self) -> Option<String> {
+        FILES.chars().nth(self.file).map(String::from)
     }
 
+    /// The rank as it is written, or `None` when it is off the board.
+    pub fn rank_notation(&self) -> Option<String> {
+        (self.rank < self.height).then(|| format!("{}", self.height - self.rank))
+    }
+
+    /// The piece, file and rank, like `Kd4`. Squares off the board are
+    /// written as their file and rank instead, like `K(9,0)`.
     pub fn notation(&self) -> String {
-        format!(
-            "{}{}{}",
-            self.piece_notation(),
-            self.file_notation(),
-            BOARD_SIZE - self.rank
-        )
+        match (self.file_notation(), self.rank_notation()) {
+            (Some(file), Some(rank)) => format!("{}{}{}", self.piece_notation(), file, rank),
+            _ => format!("{}({},{})", self.piece_notation(), self.file, self.rank),
+        }
     }
 
     pub fn is_occupied(&self) -> bool {
@@ -70,10 +114,9 @@ impl Square {
     }
 
     fn piece_notation(&self) -> String {
-        if self.piece.is_none() {
-            "".to_string()
-        } else {
-            self.piece.unwrap().notation()
+        match self.piece {
+            Some(piece) => piece.notation(),
+            None => "".to_string(),
         }
     }
 }
@@ -115,6 +158,22 @@ impl SquarePair {
     }
 }
 
+impl PartialEq for Square {
+    fn eq(&self, other: &Self) -> bool {
+        self.file == other.file && self.rank == other.rank && self.piece == other.piece
+    }
+}
+
+impl Eq for Square {}
+
+impl Hash for Square {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.file.hash(state);
+        self.rank.hash(state);
+        self.piece.hash(state);
+    }
+}
+
 impl fmt::Debug for Square {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}({},{})", self.notation(), self.file, self.rank)
@@ -128,7 +187,7 @@ mod tests {
     macro_rules! validate_square {
         ($notation:literal, $file:expr, $rank:expr) => {
             let notation = format!("{}{}", "K", $notation);
-            let square = Square::parse(&notation);
+            let square = Square::parse(&notation).unwrap();
             assert_eq!(square.file, $file);
             assert_eq!(square.rank, $rank);
             assert_eq!(square.piece, Some(Piece::King));
@@ -155,4 +214,47 @@ mod tests {
         validate_square!("d3", 3, 1);
         validate_square!("d4", 3, 0);
     }
+
+    #[test]
+    fn test_square_parse_with_height() {
+        let square = Square::parse_with_height("Kh8", 8).unwrap();
+        assert_eq!(square.file, 7);
+        assert_eq!(square.rank, 0);
+        assert_eq!(square.notation(), "Kh8");
+
+        let square = Square::parse_with_height("Pe1", 5).unwrap();
+        assert_eq!(square.file, 4);
+        assert_eq!(square.rank, 4);
+        assert_eq!(square.notation(), "Pe1");
+    }
+
+    #[test]
+    fn test_square_parse_errors() {
+        let error = |notation: &str| match Square::parse(notation) {
+            Err(SError::InvalidSquare { position, .. }) => position,
+            _ => panic!("{} should not parse", notation),
+        };
+
+        assert_eq!(0, error(""));
+        assert_eq!(0, error("Xa1"));
+        assert_eq!(1, error("K"));
+        assert_eq!(1, error("Kz1"));
+        assert_eq!(2, error("Ka"));
+        assert_eq!(2, error("Kax"));
+        assert_eq!(2, error("Ka5"));
+        assert_eq!(2, error("Ka0"));
+        assert_eq!(3, error("Ka12"));
+        assert!(Square::parse_with_height("Ka5", 5).is_ok());
+    }
+
+    #[test]
+    fn test_square_off_board() {
+        assert_eq!("(9,0)", Square::new(9, 0, None).notation());
+        assert_eq!(None, Square::new(9, 0, None).file_notation());
+        assert_eq!("K(0,4)", Square::new(0, 4, Some(Piece::King)).notation());
+        assert_eq!(
+            Square::with_height(0, 0, None, 5),
+            Square::with_height(0, 0, None, 4)
+        );
+    }
 }
@@ -0,0 +1,123 @@
+// Boards that are the same puzzle turned around. Mirroring the files never
+// changes which captures can be made. Flipping the ranks, and on square boards
+// turning the board on its side, only keeps the same captures while there are
+// no pawns, as pawns only capture towards the top of the board.
+
+use super::{Board, piece::Piece};
+
+impl Board {
+    /// Every board that plays the same as this one, starting with this board.
+    pub fn symmetries(&self) -> Vec<Board> {
+        let has_pawns = self
+            .cells
+            .iter()
+            .flatten()
+            .any(|piece| *piece == Some(Piece::Pawn));
+        let transposes: &[bool] = match (has_pawns, self.width == self.height) {
+            (false, true) => &[false, true],
+            _ => &[false],
+        };
+        let rank_flips: &[bool] = if has_pawns { &[false] } else { &[false, true] };
+
+        let mut boards = Vec::new();
+        for &transpose in transposes {
+            for &flip_ranks in rank_flips {
+                for flip_files in [false, true] {
+                    boards.push(self.transformed(transpose, flip_files, flip_ranks));
+                }
+            }
+        }
+        boards
+    }
+
+    /// The board among the `symmetries` of this board with the smallest
+    /// board string, so boards that are the same puzzle turned around share
+    /// it.
+    pub fn canonical(&self) -> Board {
+        self.symmetries()
+            .into_iter()
+            .min_by_key(|board| board.board_string())
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// The `Board::id` of `canonical`, or `None` when the board has too many
+    /// pieces for a unique id.
+    pub fn canonical_id(&self) -> Option<u128> {
+        self.has_unique_id().then(|| self.canonical().id())
+    }
+
+    fn transformed(&self, transpose: bool, flip_files: bool, flip_ranks: bool) -> Board {
+        let (width, height) = if transpose {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        let mut board = Board::empty(width, height);
+        for file in 0..self.width {
+            for rank in 0..self.height {
+                let Some(piece) = self.cells[file][rank] else {
+                    continue;
+                };
+
+                let (mut to_file, mut to_rank) = if transpose {
+                    (rank, file)
+                } else {
+                    (file, rank)
+                };
+                if flip_files {
+                    to_file = width - 1 - to_file;
+                }
+                if flip_ranks {
+                    to_rank = height - 1 - to_rank;
+                }
+                board.set(board.square(to_file, to_rank, Some(piece)));
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::Solver;
+
+    use super::*;
+
+    #[test]
+    fn symmetries() {
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let symmetries = board.symmetries();
+        assert_eq!(2, symmetries.len());
+        assert_eq!("...N.R.PPN.K....", symmetries[1].board_string());
+
+        let board = Board::from_string("Q.N/.R./B../...".to_string()).unwrap();
+        let symmetries = board.symmetries();
+        assert_eq!(4, symmetries.len());
+        for symmetry in &symmetries {
+            assert_eq!(
+                Solver::new(board.clone()).count_solutions(),
+                Solver::new(symmetry.clone()).count_solutions()
+            );
+        }
+
+        let board = Board::from_string("Q..N.R..B.......".to_string()).unwrap();
+        assert_eq!(8, board.symmetries().len());
+        let turned = &board.symmetries()[5];
+        assert_ne!(board.id(), turned.id());
+        assert_eq!(board.canonical_id(), turned.canonical_id());
+        assert!(board.canonical_id().is_some());
+
+        // Too many pieces for an id, the board strings still tell them apart
+        let crowded = format!("{}{}", "N".repeat(20), ".".repeat(44));
+        let crowded = Board::from_string(crowded).unwrap();
+        let other = format!("{}B{}{}", "N".repeat(15), "N".repeat(4), ".".repeat(44));
+        let other = Board::from_string(other).unwrap();
+        assert_eq!(crowded.id(), other.id());
+        assert_eq!(None, crowded.canonical_id());
+        assert_ne!(
+            crowded.canonical().board_string(),
+            other.canonical().board_string()
+        );
+    }
+}
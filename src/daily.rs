@@ -0,0 +1,246 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    board::{Board, errors::SError},
+    difficulty::Difficulty,
+    generator::{self, DifficultyTarget, random::SeededRandom},
+};
+
+/// A day on the calendar. Daily puzzles change at midnight UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+/// The puzzle everyone gets on `date`.
+pub struct DailyPuzzle {
+    pub date: Date,
+    pub seed: u64,
+    pub board: Board,
+
+    /// Not set on the rare days the generator misses the day's difficulty
+    /// range and settles for a puzzle with the day's number of pieces.
+    pub difficulty: Option<Difficulty>,
+}
+
+impl Date {
+    /// `None` when there is no such day, like the 30th of February.
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let date = Date { year, month, day };
+        if Date::from_days(date.days()) != date {
+            return None;
+        }
+
+        Some(date)
+    }
+
+    /// Parses a date written as `YYYY-MM-DD`.
+    pub fn parse(date: &str) -> Option<Self> {
+        let mut parts = date.split('-');
+        let year = parts.next()?;
+        let month = parts.next()?;
+        let day = parts.next()?;
+        if parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+
+        Date::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    /// The day `seconds` after midnight UTC on 1970-01-01.
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        Date::from_days(seconds.div_euclid(24 * 60 * 60))
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday
+        match (self.days() + 3).rem_euclid(7) {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+
+    // Days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    fn days(&self) -> i64 {
+        let month = self.month as i64;
+        let year = self.year as i64 - if month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Date {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+}
+
+// Puzzles tried with the day's number of pieces when no puzzle lands in the
+// day's difficulty range
+const FALLBACK_ATTEMPTS: u32 = 100;
+
+/// The seed the puzzle for `date` is generated from.
+pub fn seed(date: Date) -> u64 {
+    SeededRandom::new(date.days() as u64).next_u64()
+}
+
+/// How hard puzzles are on each day of the week, from easy on Monday to hard
+/// on Sunday. Ratings are the ones given by `difficulty::rate`.
+pub fn rating_range(weekday: Weekday) -> RangeInclusive<f64> {
+    match weekday {
+        Weekday::Monday => 0.0..=4.0,
+        Weekday::Tuesday => 3.0..=5.5,
+        Weekday::Wednesday => 4.5..=7.0,
+        Weekday::Thursday => 6.0..=8.5,
+        Weekday::Friday => 7.5..=10.0,
+        Weekday::Saturday => 9.0..=12.0,
+        Weekday::Sunday => 10.0..=14.0,
+    }
+}
+
+/// Generates the puzzle for `date`. Every call with the same date gives the
+/// same puzzle, on every platform. Fails with `SError::NoPuzzleGenerated` if
+/// no puzzle could be generated for the day at all.
+pub fn puzzle(date: Date) -> Result<DailyPuzzle, SError> {
+    let seed = seed(date);
+    let rand = SeededRandom::new(seed);
+    let weekday = date.weekday();
+
+    // No time budget, it would make the puzzle depend on how fast the
+    // player's machine is
+    let target = DifficultyTarget::new(rating_range(weekday));
+    if let Ok(generate) = generator::generate_with_difficulty(&target, &rand) {
+        let difficulty = generate.difficulty().cloned();
+        if let Some(board) = generate.board() {
+            return Ok(DailyPuzzle {
+                date,
+                seed,
+                board,
+                difficulty,
+            });
+        }
+    }
+
+    let num_pieces = match weekday {
+        Weekday::Monday => 3,
+        Weekday::Tuesday | Weekday::Wednesday => 4,
+        Weekday::Thursday | Weekday::Friday => 5,
+        Weekday::Saturday => 6,
+        Weekday::Sunday => 7,
+    };
+    for _ in 0..FALLBACK_ATTEMPTS {
+        if let Some(board) = generator::generate(num_pieces, 100, &rand)?.board() {
+            return Ok(DailyPuzzle {
+                date,
+                seed,
+                board,
+                difficulty: None,
+            });
+        }
+    }
+
+    Err(SError::NoPuzzleGenerated)
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Weekday {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_dates() {
+        let date = Date::parse("2024-02-29").expect("leap day should parse");
+        assert_eq!(Date::new(2024, 2, 29), Some(date));
+        assert_eq!("2024-02-29", date.to_string());
+        assert_eq!(Weekday::Thursday, date.weekday());
+
+        assert_eq!(None, Date::parse("2023-02-29"));
+        assert_eq!(None, Date::parse("2023-13-01"));
+        assert_eq!(None, Date::parse("2023-1-01"));
+        assert_eq!(None, Date::parse("yesterday"));
+
+        let epoch = Date::new(1970, 1, 1).unwrap();
+        assert_eq!(epoch, Date::from_unix_seconds(0));
+        assert_eq!(epoch, Date::from_unix_seconds(24 * 60 * 60 - 1));
+        assert_eq!(Date::new(1969, 12, 31), Some(Date::from_unix_seconds(-1)));
+        assert_eq!(Weekday::Thursday, epoch.weekday());
+        assert_eq!(
+            Date::new(2000, 3, 1),
+            Some(Date::from_unix_seconds(951868800))
+        );
+    }
+
+    #[test]
+    fn daily_puzzle() {
+        // A Monday
+        let date = Date::parse("2024-03-04").unwrap();
+        assert_eq!(Weekday::Monday, date.weekday());
+
+        let first = puzzle(date).unwrap();
+        let second = puzzle(date).unwrap();
+        assert_eq!(first.seed, second.seed);
+        assert_eq!(first.board.id(), second.board.id());
+
+        let rating = first.difficulty.expect("puzzle should be rated").rating;
+        assert!(rating_range(Weekday::Monday).contains(&rating));
+
+        let next_day = puzzle(Date::parse("2024-03-05").unwrap()).unwrap();
+        assert_ne!(first.seed, next_day.seed);
+    }
+}
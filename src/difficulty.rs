@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::board::{Board, BoardState, cmove::CMove, piece::Piece};
+
+/// How hard a puzzle is, along with what went into the rating.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Difficulty {
+    /// Higher is harder. 3 piece puzzles tend to rate between 2 and 5, and
+    /// 7 piece puzzles around 10.
+    pub rating: f64,
+    pub breakdown: Breakdown,
+}
+
+/// Features of a puzzle that make it harder or easier to solve.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Breakdown {
+    pub pieces: usize,
+
+    /// Sequences of moves that win the game.
+    pub winning_sequences: f64,
+
+    /// Every sequence of moves that can be played until the game is over.
+    pub total_sequences: f64,
+
+    /// Average number of legal moves in the positions that can be reached.
+    pub branching_factor: f64,
+
+    /// The most moves that can still be played after a capture that loses the
+    /// game, before the board shows the game is lost.
+    pub trap_depth: usize,
+
+    /// Captures that have to be found on the way to a win, because they are
+    /// the only capture that keeps the game winnable. Counted along the
+    /// solution with the fewest of them.
+    pub forced_moves: usize,
+
+    /// How confusing the pieces on the board are, between 0 and 1. Knights
+    /// are the most confusing, queens the least.
+    pub piece_mix: f64,
+}
+
+impl Breakdown {
+    /// The chance that playing random legal moves wins the game.
+    pub fn win_ratio(&self) -> f64 {
+        self.winning_sequences / self.total_sequences
+    }
+}
+
+/// Rates how hard `board` is to solve from its current position. Returns
+/// `None` when the game can't be won from there.
+///
+/// The rating adds up the breakdown: twice the log of the odds against winning
+/// by playing at random, the branching factor above 1, the trap depth, the
+/// forced moves and twice the piece mix.
+pub fn rate(board: &Board) -> Option<Difficulty> {
+    let mut explorer = Explorer::default();
+    let mut board = board.clone();
+    let start = explorer.explore(&mut board);
+    if start.wins == 0.0 {
+        return None;
+    }
+
+    let pieces = pieces(&board);
+    let branching_factor = match explorer.positions {
+        0 => 0.0,
+        positions => explorer.total_moves as f64 / positions as f64,
+    };
+    let piece_mix = match pieces.len() {
+        0 => 0.0,
+        count => pieces.iter().map(|p| confusion(*p)).sum::<f64>() / count as f64,
+    };
+
+    let breakdown = Breakdown {
+        pieces: pieces.len(),
+        winning_sequences: start.wins,
+        total_sequences: start.sequences,
+        branching_factor,
+        trap_depth: explorer.trap_depth,
+        forced_moves: start.forced,
+        piece_mix,
+    };
+
+    let rating = 2.0 * (1.0 / breakdown.win_ratio()).ln()
+        + f64::max(0.0, branching_factor - 1.0)
+        + breakdown.trap_depth as f64
+        + breakdown.forced_moves as f64
+        + 2.0 * piece_mix;
+
+    Some(Difficulty { rating, breakdown })
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    sequences: f64,
+    wins: f64,
+
+    // Most moves that can be played from this position before the game ends
+    longest: usize,
+
+    // Fewest forced moves on the way to a win from this position
+    forced: usize,
+}
+
+#[derive(Default)]
+struct Explorer {
+    nodes: HashMap<u128, Node>,
+    positions: usize,
+    total_moves: usize,
+    trap_depth: usize,
+}
+
+impl Explorer {
+    fn explore(&mut self, board: &mut Board) -> Node {
+        match board.game_state {
+            BoardState::Won => {
+                return Node {
+                    sequences: 1.0,
+                    wins: 1.0,
+                    longest: 0,
+                    forced: 0,
+                };
+            }
+            BoardState::InProgress => {}
+            _ => {
+                return Node {
+                    sequences: 1.0,
+                    wins: 0.0,
+                    longest: 0,
+                    forced: 0,
+                };
+            }
+        }
+
+        if board.has_unique_id()
+            && let Some(node) = self.nodes.get(&board.id())
+        {
+            return *node;
+        }
+
+        let moves: Vec<CMove> = board.legal_moves.iter().cloned().collect();
+        let mut children = Vec::with_capacity(moves.len());
+        for m in moves {
+            board.play(m);
+            children.push(self.explore(board));
+            board.unmake_move();
+        }
+
+        let winning: Vec<&Node> = children.iter().filter(|c| c.wins > 0.0).collect();
+        let forced = match winning.len() {
+            0 => 0,
+            1 => 1 + winning[0].forced,
+            _ => winning.iter().map(|c| c.forced).min().unwrap_or(0),
+        };
+
+        let node = Node {
+            sequences: children.iter().map(|c| c.sequences).sum(),
+            wins: winning.iter().map(|c| c.wins).sum(),
+            longest: 1 + children.iter().map(|c| c.longest).max().unwrap_or(0),
+            forced,
+        };
+
+        self.positions += 1;
+        self.total_moves += children.len();
+        if node.wins > 0.0 {
+            for child in children.iter().filter(|c| c.wins == 0.0) {
+                self.trap_depth = usize::max(self.trap_depth, child.longest);
+            }
+        }
+
+        if board.has_unique_id() {
+            self.nodes.insert(board.id(), node);
+        }
+
+        node
+    }
+}
+
+fn pieces(board: &Board) -> Vec<Piece> {
+    board.cells.iter().flatten().filter_map(|p| *p).collect()
+}
+
+fn confusion(piece: Piece) -> f64 {
+    match piece {
+        Piece::Knight => 1.0,
+        Piece::Bishop => 0.75,
+        Piece::Pawn => 0.5,
+        Piece::Rook => 0.5,
+        Piece::King => 0.5,
+        Piece::Queen => 0.25,
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let b = &self.breakdown;
+        writeln!(f, "{:>30}:{:>8.2}", "Difficulty", self.rating)?;
+        writeln!(f, "{:>30}:{:>8}", "Pieces", b.pieces)?;
+        writeln!(f, "{:>30}:{:>8}", "Winning sequences", b.winning_sequences)?;
+        writeln!(f, "{:>30}:{:>8}", "Total sequences", b.total_sequences)?;
+        writeln!(f, "{:>30}:{:>8.4}", "Win ratio", b.win_ratio())?;
+        writeln!(f, "{:>30}:{:>8.2}", "Branching factor", b.branching_factor)?;
+        writeln!(f, "{:>30}:{:>8}", "Trap depth", b.trap_depth)?;
+        writeln!(f, "{:>30}:{:>8}", "Forced moves", b.forced_moves)?;
+        writeln!(f, "{:>30}:{:>8.2}", "Piece mix", b.piece_mix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn difficulty_smoke() {
+        // N . . .
+        // P . R .
+        // K . N P
+        // . . . .
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let difficulty = rate(&board).expect("puzzle should be solvable");
+        let breakdown = &difficulty.breakdown;
+        assert_eq!(6, breakdown.pieces);
+        assert_eq!(3.0, breakdown.winning_sequences);
+        assert!(breakdown.total_sequences > breakdown.winning_sequences);
+        assert!(breakdown.branching_factor >= 1.0);
+        assert!(breakdown.trap_depth >= 1);
+        assert!(difficulty.rating > 0.0);
+
+        // Fewer pieces make for an easier puzzle
+        let easy = Board::from_string("N...R...........".to_string()).unwrap();
+        let easy = rate(&easy).expect("puzzle should be solvable");
+        assert_eq!(1.0, easy.breakdown.win_ratio());
+        assert_eq!(0, easy.breakdown.trap_depth);
+        assert!(easy.rating < difficulty.rating);
+    }
+
+    #[test]
+    fn difficulty_unsolvable() {
+        let board = Board::from_string("P..P.PP..PP.P..P".to_string()).unwrap();
+        assert!(rate(&board).is_none());
+    }
+}
@@ -6,10 +6,14 @@ use std::{
 use button::Button;
 use color::UiColor;
 use macroquad::{audio, math, prelude::*, rand};
+use miniquad::date;
 use shadow::draw_shadow;
 use sol_chess::{
-    board::{Board, BoardState},
-    generator::{self, RandomRange},
+    board::{Board, BoardState, cmove::CMove, errors::SError},
+    daily::{self, Date},
+    generator::{self, DifficultyTarget, config::GeneratorConfig, random::SeededRandom},
+    pack::Pack,
+    solver::Solver,
 };
 use sound::Sounds;
 use texture::PieceTexture;
@@ -20,20 +24,49 @@ pub mod shadow;
 pub mod sound;
 pub mod texture;
 
-pub struct MacroquadRandAdapter;
-impl RandomRange for MacroquadRandAdapter {
-    fn gen_range(&self, min: usize, max: usize) -> usize {
-        rand::gen_range(min, max)
-    }
-}
+const UNWINNABLE_WARNING: &str = "There is no way to win from here";
+const NO_PUZZLE_MESSAGE: &str = "Could not generate a puzzle, try again";
+const NO_PACK_PUZZLE_MESSAGE: &str = "The pack has no 4x4 puzzles";
+
+// Played when the first puzzle can't be generated, so there is always a board
+const FALLBACK_BOARD: &str = "N...P.R.K.NP....";
 
 pub struct Game {
     // The generated puzzle. We keep a copy of this to reset the game.
     original_board: Board,
+    rating: Option<f64>,
+
+    // Generating with the same seed and mode gives the same puzzle again
+    seed: u64,
+    daily_date: Option<Date>,
+
+    // Puzzles loaded from a pack, played in order in the pack mode
+    pack: Option<Pack>,
+    pack_entry: usize,
+    title: Option<String>,
 
     // What is shown to the user
     board: Board,
 
+    // Moves taken back with undo, most recently undone last. Cleared as soon
+    // as a new move is made.
+    redo_moves: Vec<CMove>,
+
+    // A winning move from the current position, once the player asks for a
+    // hint. Its source is shown first, and its target too on a second ask.
+    hint: Option<CMove>,
+    hint_shows_target: bool,
+    message: Option<String>,
+
+    // Searches the positions of the current puzzle for hints and warnings.
+    // Kept for the whole puzzle, so positions that were already searched are
+    // answered from its cache after every capture, undo and redo.
+    solver: Solver,
+
+    // Tell the player as soon as a capture leaves no way to win. Some players
+    // would rather find out for themselves.
+    warn_unwinnable: bool,
+
     // Constants througout the game
     texture_res: Texture2D,
     sounds: Sounds,
@@ -54,6 +87,7 @@ pub struct Game {
     squares: Vec<GameSquare>,
     heading_rect: Rect,
     heading_font_size: f32,
+    message_rect: Rect,
     gp_btns: HashMap<ButtonAction, Button>,
     mode_btns: HashMap<GameMode, Button>,
     rules: bool,
@@ -73,6 +107,10 @@ struct GameSquare {
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum ButtonAction {
     Reset,
+    Undo,
+    Redo,
+    Hint,
+    Warnings,
     Next,
 }
 
@@ -81,6 +119,17 @@ pub enum GameMode {
     Easy,
     Medium,
     Hard,
+    Daily,
+    Pack,
+}
+
+struct Puzzle {
+    board: Board,
+    rating: Option<f64>,
+    seed: u64,
+    daily_date: Option<Date>,
+    pack_entry: Option<usize>,
+    title: Option<String>,
 }
 
 #[derive(Copy, Clone)]
@@ -91,19 +140,49 @@ enum GameState {
 }
 
 impl Game {
-    pub fn new(texture_res: Texture2D, sounds: Sounds) -> Self {
+    /// Starts on the first puzzle of `pack` when there is one, and on a
+    /// generated puzzle otherwise.
+    pub fn new(texture_res: Texture2D, sounds: Sounds, pack: Option<Pack>) -> Self {
         let num_squares: usize = 4;
-        let game_mode = GameMode::Medium;
-        let board = Game::generate_puzzle(game_mode);
+        let pack_puzzle = pack.as_ref().and_then(|pack| Game::pack_puzzle(pack, 0));
+        let game_mode = match pack_puzzle {
+            Some(_) => GameMode::Pack,
+            None => GameMode::Medium,
+        };
+        let puzzle = pack_puzzle
+            .ok_or(SError::NoPuzzleGenerated)
+            .or_else(|_| Game::generate_puzzle(game_mode))
+            .unwrap_or_else(|_| Puzzle {
+                board: Board::from_string(FALLBACK_BOARD.to_string())
+                    .expect("Fallback board should be valid"),
+                rating: None,
+                seed: 0,
+                daily_date: None,
+                pack_entry: None,
+                title: None,
+            });
 
         Self {
-            original_board: board.clone(),
-            board,
+            original_board: puzzle.board.clone(),
+            solver: Solver::new(puzzle.board.clone()),
+            rating: puzzle.rating,
+            seed: puzzle.seed,
+            daily_date: puzzle.daily_date,
+            pack,
+            pack_entry: puzzle.pack_entry.unwrap_or(0),
+            title: puzzle.title,
+            board: puzzle.board,
+            redo_moves: Vec::new(),
+            hint: None,
+            hint_shows_target: false,
+            message: None,
+            warn_unwinnable: true,
             board_rect: Rect::new(0., 0., 0., 0.),
             squares: Vec::new(),
             heading_rect: Rect::new(0., 0., 0., 0.),
             heading_text: "Solitaire Chess".to_string(),
             heading_font_size: 0.,
+            message_rect: Rect::new(0., 0., 0., 0.),
             num_squares,
             texture_res,
             sounds,
@@ -125,6 +204,8 @@ impl Game {
         self.draw_heading();
         self.draw_board();
         self.draw_buttons();
+        self.draw_message();
+        self.draw_seed();
         self.draw_debug();
     }
 
@@ -145,7 +226,7 @@ impl Game {
         for btn in &mut self.gp_btns {
             btn.1.handle_input();
             if btn.1.is_clicked() {
-                gp_btn_clicked = Some(btn.0.clone());
+                gp_btn_clicked = Some(*btn.0);
                 break;
             }
         }
@@ -153,6 +234,10 @@ impl Game {
         if let Some(action) = gp_btn_clicked {
             match action {
                 ButtonAction::Reset => self.reset(),
+                ButtonAction::Undo => self.undo(),
+                ButtonAction::Redo => self.redo(),
+                ButtonAction::Hint => self.show_hint(),
+                ButtonAction::Warnings => self.toggle_warnings(),
                 ButtonAction::Next => self.next_puzzle(),
             }
         } else {
@@ -192,11 +277,18 @@ impl Game {
         }
 
         for btn in &mut self.mode_btns {
-            if self.game_mode == *btn.0 {
-                btn.1.is_active = false;
+            btn.1.is_active = self.game_mode != *btn.0;
+        }
+
+        self.update_gameplay_buttons();
+
+        if is_key_released(KeyCode::Z) {
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                self.redo();
             } else {
-                btn.1.is_active = true;
+                self.undo();
             }
+            return;
         }
 
         if is_key_released(KeyCode::Escape) {
@@ -214,7 +306,7 @@ impl Game {
         }
 
         if is_mouse_button_released(MouseButton::Left) {
-            let current_state = self.state.clone();
+            let current_state = self.state;
             let new_state = match current_state {
                 GameState::SelectSource(previous_target) => {
                     self.handle_select_source(mouse_position(), previous_target)
@@ -236,7 +328,7 @@ impl Game {
         }
 
         if is_mouse_button_pressed(MouseButton::Left) {
-            let current_state = self.state.clone();
+            let current_state = self.state;
             let new_state = match current_state {
                 GameState::SelectSource(previous_target) => {
                     self.handle_select_source(mouse_position(), previous_target)
@@ -261,6 +353,42 @@ impl Game {
         );
     }
 
+    fn draw_seed(&self) {
+        let seed = match (&self.title, self.daily_date) {
+            (Some(title), _) => title.clone(),
+            (None, Some(date)) => format!("Daily {}, seed {}", date, self.seed),
+            (None, None) => format!("Seed {}", self.seed),
+        };
+        let font_size = self.heading_font_size * 0.4;
+        let dims = measure_text(&seed, None, font_size as u16, 1.0);
+        let heading_bottom = self.heading_rect.y + self.heading_rect.h;
+        draw_text(
+            &seed,
+            self.board_rect.x + (self.board_rect.w - dims.width) / 2.0,
+            heading_bottom
+                + (self.board_rect.y - heading_bottom - dims.height) / 2.0
+                + dims.offset_y,
+            font_size,
+            DARKGRAY,
+        );
+    }
+
+    fn draw_message(&self) {
+        let Some(message) = &self.message else {
+            return;
+        };
+
+        let font_size = self.heading_font_size * 0.5;
+        let dims = measure_text(message, None, font_size as u16, 1.0);
+        draw_text(
+            message,
+            self.message_rect.x + (self.message_rect.w - dims.width) / 2.0,
+            self.message_rect.y + dims.offset_y,
+            font_size,
+            BLACK,
+        );
+    }
+
     fn draw_board(&self) {
         let board_shadow_width = 0.1 * self.square_width;
         draw_shadow(self.board_rect, board_shadow_width);
@@ -299,7 +427,10 @@ impl Game {
                 true => square.color,
                 false => match square.is_target {
                     true => UiColor::Pink.to_shadow_color(),
-                    false => square.color,
+                    false => match self.is_hinted(square.i, square.j) {
+                        true => UiColor::Blue.to_shadow_color(),
+                        false => square.color,
+                    },
                 },
             };
 
@@ -328,17 +459,17 @@ impl Game {
             }
         });
 
-        if let Some(selected_square) = selected_square {
-            if let Some(p) = self.board.cells[selected_square.i][selected_square.j] {
-                let dtp = PieceTexture::for_piece(p, sprite_size);
-                draw_texture_ex(
-                    &self.texture_res,
-                    mouse_position().0 - sprite_size / 2.0,
-                    mouse_position().1 - sprite_size / 2.0,
-                    WHITE,
-                    dtp,
-                );
-            }
+        if let Some(selected_square) = selected_square
+            && let Some(p) = self.board.cells[selected_square.i][selected_square.j]
+        {
+            let dtp = PieceTexture::for_piece(p, sprite_size);
+            draw_texture_ex(
+                &self.texture_res,
+                mouse_position().0 - sprite_size / 2.0,
+                mouse_position().1 - sprite_size / 2.0,
+                WHITE,
+                dtp,
+            );
         }
     }
 
@@ -362,13 +493,13 @@ impl Game {
             let (mx, my) = mouse_position();
             let hover_square = self.squares.iter().find(|s| {
                 let c = Circle::new(mx, my, 0.0);
-                if c.overlaps_rect(&s.rect) {
-                    return true;
-                }
-                return false;
+                c.overlaps_rect(&s.rect)
             });
             debug_lines.push(format!("Game State: {}", self.state));
             debug_lines.push(format!("Board State: {}", self.board.game_state));
+            if let Some(rating) = self.rating {
+                debug_lines.push(format!("Difficulty: {:.2}", rating));
+            }
             if let Some(hover_square) = hover_square {
                 debug_lines.push(format!("Hover: [ {}, {} ]", hover_square.i, hover_square.j));
             }
@@ -431,17 +562,39 @@ impl Game {
         let btn_w = board_width * 0.2;
 
         let btn_y = board_width + board_y + 0.3 * self.square_width;
-        let btn_x_offset = 0.5 * (board_width / 2. - btn_w);
+        let btn_x_offset = 0.5 * (board_width / 4. - btn_w);
         let reset_btn = Button::new(
             "Reset",
             Rect::new(board_x + btn_x_offset, btn_y, btn_w, btn_h),
             UiColor::Yellow,
             self.sounds.button.clone(),
         );
+        let undo_btn = Button::new(
+            "Undo",
+            Rect::new(
+                board_x + (0.25 * board_width) + btn_x_offset,
+                btn_y,
+                btn_w,
+                btn_h,
+            ),
+            UiColor::Yellow,
+            self.sounds.button.clone(),
+        );
+        let redo_btn = Button::new(
+            "Redo",
+            Rect::new(
+                board_x + (0.5 * board_width) + btn_x_offset,
+                btn_y,
+                btn_w,
+                btn_h,
+            ),
+            UiColor::Yellow,
+            self.sounds.button.clone(),
+        );
         let mut next_btn = Button::new(
             "Next",
             Rect::new(
-                board_x + (0.5 * board_width) + btn_x_offset,
+                board_x + (0.75 * board_width) + btn_x_offset,
                 btn_y,
                 btn_w,
                 btn_h,
@@ -449,7 +602,9 @@ impl Game {
             UiColor::Green,
             self.sounds.button.clone(),
         );
-        next_btn.is_active = false;
+        next_btn.is_active = self.board.game_state == BoardState::Won;
+
+        self.message_rect = Rect::new(board_x, btn_y + 1.5 * btn_h, board_width, btn_h);
 
         let rules_button = Button::new(
             "Rules",
@@ -467,6 +622,37 @@ impl Game {
         self.gp_btns = HashMap::new();
         self.gp_btns.insert(ButtonAction::Next, next_btn);
         self.gp_btns.insert(ButtonAction::Reset, reset_btn);
+        self.gp_btns.insert(ButtonAction::Undo, undo_btn);
+        self.gp_btns.insert(ButtonAction::Redo, redo_btn);
+
+        let mut hint_btn = Button::new(
+            "Hint",
+            Rect::new(
+                board_x + board_width + (board_x - btn_w) / 2.,
+                board_y + (self.square_width - btn_h) / 2.,
+                btn_w,
+                btn_h,
+            ),
+            UiColor::Blue,
+            self.sounds.button.clone(),
+        );
+        hint_btn.is_active = self.board.game_state != BoardState::Won;
+        self.gp_btns.insert(ButtonAction::Hint, hint_btn);
+
+        let mut warnings_btn = Button::new(
+            "",
+            Rect::new(
+                board_x + board_width + (board_x - btn_w) / 2.,
+                board_y + self.square_width + (self.square_width - btn_h) / 2.,
+                btn_w,
+                btn_h,
+            ),
+            UiColor::Green,
+            self.sounds.button.clone(),
+        );
+        Game::style_warnings_button(&mut warnings_btn, self.warn_unwinnable);
+        self.gp_btns.insert(ButtonAction::Warnings, warnings_btn);
+        self.update_gameplay_buttons();
 
         let easy_btn = Button::new(
             "Easy",
@@ -509,6 +695,34 @@ impl Game {
         self.mode_btns.insert(GameMode::Medium, medium_btn);
         self.mode_btns.insert(GameMode::Hard, hard_button);
 
+        let daily_button = Button::new(
+            "Daily",
+            Rect::new(
+                board_x + board_width + (board_x - btn_w) / 2.,
+                board_y + 3. * self.square_width + (self.square_width - btn_h) / 2.,
+                btn_w,
+                btn_h,
+            ),
+            UiColor::Yellow,
+            self.sounds.mode.clone(),
+        );
+        self.mode_btns.insert(GameMode::Daily, daily_button);
+
+        if self.pack.is_some() {
+            let pack_button = Button::new(
+                "Pack",
+                Rect::new(
+                    board_x + board_width + (board_x - btn_w) / 2.,
+                    board_y + 2. * self.square_width + (self.square_width - btn_h) / 2.,
+                    btn_w,
+                    btn_h,
+                ),
+                UiColor::Yellow,
+                self.sounds.mode.clone(),
+            );
+            self.mode_btns.insert(GameMode::Pack, pack_button);
+        }
+
         for btn in &mut self.mode_btns {
             btn.1.is_active = true;
             if self.game_mode == *btn.0 {
@@ -527,10 +741,8 @@ impl Game {
         let mouse = Circle::new(x, y, 0.0);
         let mut selected = None;
         for square in &mut self.squares {
-            if mouse.overlaps_rect(&square.rect) {
-                if let Some(_) = self.board.cells[square.i][square.j] {
-                    selected = Some((square.i, square.j));
-                }
+            if mouse.overlaps_rect(&square.rect) && self.board.cells[square.i][square.j].is_some() {
+                selected = Some((square.i, square.j));
             }
         }
 
@@ -554,7 +766,7 @@ impl Game {
             self.get(i, j).is_previous_target = true;
         }
 
-        return GameState::SelectSource(None);
+        GameState::SelectSource(None)
     }
 
     fn handle_select_target(
@@ -567,10 +779,8 @@ impl Game {
 
         let mut selected = None;
         for square in &mut self.squares {
-            if mouse.overlaps_rect(&square.rect) {
-                if let Some(_) = self.board.cells[square.i][square.j] {
-                    selected = Some((square.i, square.j));
-                }
+            if mouse.overlaps_rect(&square.rect) && self.board.cells[square.i][square.j].is_some() {
+                selected = Some((square.i, square.j));
             }
         }
 
@@ -595,8 +805,14 @@ impl Game {
                 m.from.file == s_x && m.from.rank == s_y && m.to.file == x && m.to.rank == y
             });
 
-            let m = m.expect("legal move should be found");
-            self.board.make_move(m.clone());
+            let m = m.expect("legal move should be found").clone();
+            if let Err(e) = self.board.make_move(m) {
+                self.message = Some(e.to_string());
+                return GameState::SelectSource(None);
+            }
+            self.redo_moves.clear();
+            self.clear_hint();
+            self.warn_if_unwinnable();
 
             if self.board.game_state == BoardState::Won || self.board.game_state == BoardState::Lost
             {
@@ -622,11 +838,13 @@ impl Game {
         }
 
         self.reset_squares();
-        return GameState::SelectSource(None);
+        GameState::SelectSource(None)
     }
 
     fn reset(&mut self) {
         self.board = self.original_board.clone();
+        self.redo_moves.clear();
+        self.clear_hint();
         self.reset_squares();
 
         let next_button = self
@@ -638,11 +856,189 @@ impl Game {
         self.state = GameState::SelectSource(None);
     }
 
+    fn undo(&mut self) {
+        let Some(m) = self.board.unmake_move() else {
+            return;
+        };
+
+        self.redo_moves.push(m);
+        self.restore_state();
+    }
+
+    fn redo(&mut self) {
+        let Some(m) = self.redo_moves.pop() else {
+            return;
+        };
+
+        if self.board.make_move(m).is_err() {
+            self.redo_moves.clear();
+        }
+        self.restore_state();
+    }
+
+    /// Puts the game back in the state it would have been in had the moves in
+    /// the board's history just been played.
+    fn restore_state(&mut self) {
+        self.clear_hint();
+        self.warn_if_unwinnable();
+        self.reset_squares();
+        let next_button = self
+            .gp_btns
+            .get_mut(&ButtonAction::Next)
+            .expect("Cannot find next button");
+        next_button.is_active = self.board.game_state == BoardState::Won;
+
+        let previous_target = self.board.history().last().map(|m| (m.to.file, m.to.rank));
+
+        self.state = match previous_target {
+            Some(target)
+                if self.board.game_state == BoardState::Won
+                    || self.board.game_state == BoardState::Lost =>
+            {
+                GameState::GameOver(target)
+            }
+            Some((x, y)) => {
+                self.get(x, y).is_target = true;
+                GameState::SelectSource(Some((x, y)))
+            }
+            None => GameState::SelectSource(None),
+        };
+    }
+
+    fn show_hint(&mut self) {
+        if let Some(hint) = &self.hint {
+            self.hint_shows_target = true;
+            self.message = Some(format!("Capture {}", hint.notation()));
+            return;
+        }
+
+        let winning_move = self
+            .solver_at(self.board.clone())
+            .solutions_up_to(1)
+            .into_iter()
+            .next()
+            .and_then(|solution| solution.into_iter().next());
+
+        if let Some(winning_move) = winning_move {
+            self.hint = Some(winning_move);
+            self.message = None;
+            return;
+        }
+
+        self.message = Some(match self.moves_to_undo() {
+            Some(1) => "This position is lost, undo 1 move".to_string(),
+            Some(n) => format!("This position is lost, undo {} moves", n),
+            None => "This puzzle cannot be solved".to_string(),
+        });
+    }
+
+    /// How many moves need taking back before the game can be won again.
+    fn moves_to_undo(&mut self) -> Option<usize> {
+        let mut board = self.board.clone();
+        let mut moves = 0;
+        while !self.solver_at(board.clone()).is_solvable() {
+            board.unmake_move()?;
+            moves += 1;
+        }
+
+        Some(moves)
+    }
+
+    fn toggle_warnings(&mut self) {
+        self.warn_unwinnable = !self.warn_unwinnable;
+        let warnings_btn = self
+            .gp_btns
+            .get_mut(&ButtonAction::Warnings)
+            .expect("Cannot find warnings button");
+        Game::style_warnings_button(warnings_btn, self.warn_unwinnable);
+
+        if self.warn_unwinnable {
+            self.warn_if_unwinnable();
+        } else if self.message.as_deref() == Some(UNWINNABLE_WARNING) {
+            self.message = None;
+        }
+    }
+
+    fn style_warnings_button(btn: &mut Button, warn_unwinnable: bool) {
+        if warn_unwinnable {
+            btn.text = "Warn".to_string();
+            btn.color = UiColor::Green;
+        } else {
+            btn.text = "Quiet".to_string();
+            btn.color = UiColor::Grey;
+        }
+    }
+
+    fn warn_if_unwinnable(&mut self) {
+        if self.warn_unwinnable && self.solver_at(self.board.clone()).is_unwinnable() {
+            self.message = Some(UNWINNABLE_WARNING.to_string());
+        }
+    }
+
+    /// The puzzle's solver, looking at `board`.
+    fn solver_at(&mut self, board: Board) -> &Solver {
+        self.solver.board = board;
+        &self.solver
+    }
+
+    fn is_hinted(&self, i: usize, j: usize) -> bool {
+        let Some(hint) = &self.hint else {
+            return false;
+        };
+
+        (hint.from.file == i && hint.from.rank == j)
+            || (self.hint_shows_target && hint.to.file == i && hint.to.rank == j)
+    }
+
+    fn clear_hint(&mut self) {
+        self.hint = None;
+        self.hint_shows_target = false;
+        self.message = None;
+    }
+
+    fn update_gameplay_buttons(&mut self) {
+        let can_undo = !self.board.history().is_empty();
+        let can_redo = !self.redo_moves.is_empty();
+        if let Some(undo_btn) = self.gp_btns.get_mut(&ButtonAction::Undo) {
+            undo_btn.is_active = can_undo;
+        }
+
+        if let Some(redo_btn) = self.gp_btns.get_mut(&ButtonAction::Redo) {
+            redo_btn.is_active = can_redo;
+        }
+
+        let can_hint = self.board.game_state != BoardState::Won;
+        if let Some(hint_btn) = self.gp_btns.get_mut(&ButtonAction::Hint) {
+            hint_btn.is_active = can_hint;
+        }
+    }
+
     fn next_puzzle(&mut self) {
+        let puzzle = match (&self.pack, self.game_mode) {
+            (Some(pack), GameMode::Pack) => {
+                Game::pack_puzzle(pack, self.pack_entry + 1).ok_or(NO_PACK_PUZZLE_MESSAGE)
+            }
+            _ => Game::generate_puzzle(self.game_mode).map_err(|_| NO_PUZZLE_MESSAGE),
+        };
+        let puzzle = match puzzle {
+            Ok(puzzle) => puzzle,
+            Err(message) => {
+                self.message = Some(message.to_string());
+                return;
+            }
+        };
+
         self.reset();
-        let board = Game::generate_puzzle(self.game_mode);
-        self.original_board = board.clone();
-        self.board = board;
+        self.original_board = puzzle.board.clone();
+        self.solver = Solver::new(puzzle.board.clone());
+        self.board = puzzle.board;
+        self.rating = puzzle.rating;
+        self.seed = puzzle.seed;
+        self.daily_date = puzzle.daily_date;
+        self.title = puzzle.title;
+        if let Some(entry) = puzzle.pack_entry {
+            self.pack_entry = entry;
+        }
     }
 
     fn reset_squares(&mut self) {
@@ -673,15 +1069,101 @@ impl Game {
         );
     }
 
-    fn generate_puzzle(mode: GameMode) -> Board {
-        let piece_count = match mode {
-            GameMode::Easy => 3,
-            GameMode::Medium => 5,
-            GameMode::Hard => 7,
+    /// Powerful pieces make puzzles easier to read, knights and bishops make
+    /// them harder.
+    fn generator_config(mode: GameMode) -> GeneratorConfig {
+        let mut config = GeneratorConfig::new();
+        match mode {
+            GameMode::Easy => {
+                config.queen.weight = 2;
+                config.rook.weight = 2;
+            }
+            GameMode::Hard => {
+                config.knight.weight = 2;
+                config.bishop.weight = 2;
+            }
+            GameMode::Medium | GameMode::Daily | GameMode::Pack => {}
+        }
+
+        config
+    }
+
+    fn generate_puzzle(mode: GameMode) -> Result<Puzzle, SError> {
+        let (rating, piece_count) = match mode {
+            GameMode::Easy => (0.0..=4.5, 3),
+            // Pack puzzles come from `pack_puzzle`, this is only reached
+            // without a pack
+            GameMode::Medium | GameMode::Pack => (4.5..=8.0, 5),
+            GameMode::Hard => (8.0..=14.0, 7),
+            GameMode::Daily => {
+                let today = Date::from_unix_seconds(date::now() as i64);
+                let daily = daily::puzzle(today)?;
+                return Ok(Puzzle {
+                    board: daily.board,
+                    rating: daily.difficulty.map(|d| d.rating),
+                    seed: daily.seed,
+                    daily_date: Some(today),
+                    pack_entry: None,
+                    title: None,
+                });
+            }
         };
 
-        let generate = generator::generate(piece_count, 100, &MacroquadRandAdapter);
-        generate.board().expect("No puzzle was generated")
+        let seed = rand::rand() as u64;
+        let rand = SeededRandom::new(seed);
+        let mut target = DifficultyTarget::new(rating);
+        target.config = Game::generator_config(mode);
+        let generate = generator::generate_with_difficulty(&target, &rand)?;
+        let rating = generate.difficulty().map(|d| d.rating);
+        if let Some(board) = generate.board() {
+            return Ok(Puzzle {
+                board,
+                rating,
+                seed,
+                daily_date: None,
+                pack_entry: None,
+                title: None,
+            });
+        }
+
+        // Fall back to going by the number of pieces alone
+        let generate = generator::generate_with_config(&target.config, piece_count, 100, &rand)?;
+        let board = generate.board().ok_or(SError::NoPuzzleGenerated)?;
+        Ok(Puzzle {
+            board,
+            rating: None,
+            seed,
+            daily_date: None,
+            pack_entry: None,
+            title: None,
+        })
+    }
+
+    /// The first puzzle of `pack` from `entry` on that fits on the board,
+    /// starting over from the first puzzle after the last one.
+    fn pack_puzzle(pack: &Pack, entry: usize) -> Option<Puzzle> {
+        let count = pack.entries.len();
+        (0..count)
+            .map(|offset| (entry + offset) % count)
+            .find_map(|index| {
+                let puzzle = &pack.entries[index];
+                if puzzle.board.width() != 4 || puzzle.board.height() != 4 {
+                    return None;
+                }
+
+                let title = match puzzle.title.as_str() {
+                    "" => format!("Puzzle {} of {}", index + 1, count),
+                    title => format!("{} ({} of {})", title, index + 1, count),
+                };
+                Some(Puzzle {
+                    board: puzzle.board.clone(),
+                    rating: puzzle.difficulty,
+                    seed: 0,
+                    daily_date: None,
+                    pack_entry: Some(index),
+                    title: Some(title),
+                })
+            })
     }
 }
 
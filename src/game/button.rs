@@ -1,4 +1,7 @@
-use macroquad::{audio::{self, Sound}, prelude::*};
+use macroquad::{
+    audio::{self, Sound},
+    prelude::*,
+};
 
 use super::{color::UiColor, shadow::draw_shadow};
 
@@ -90,14 +93,13 @@ impl Button {
     }
 
     fn get_button_draw_offset(&self) -> f32 {
-        let button_pressed_correction = match self.is_down {
+        match self.is_down {
             true => self.shadow_width,
             false => match self.is_active {
                 true => 0.0,
                 false => self.shadow_width,
             },
-        };
-        button_pressed_correction
+        }
     }
 
     pub fn handle_input(&mut self) {
@@ -109,11 +111,9 @@ impl Button {
         let (mx, my) = mouse_position();
         let c = Circle::new(mx, my, 0.0);
 
-        if is_mouse_button_pressed(MouseButton::Left) {
-            if c.overlaps_rect(&self.rect) {
-                self.is_down = true;
-                return;
-            }
+        if is_mouse_button_pressed(MouseButton::Left) && c.overlaps_rect(&self.rect) {
+            self.is_down = true;
+            return;
         }
 
         if is_mouse_button_released(MouseButton::Left) {
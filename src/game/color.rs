@@ -2,18 +2,16 @@ use macroquad::prelude::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UiColor {
-    #[allow(dead_code)]
     Grey,
     Green,
     Pink,
     Brown,
     Yellow,
-    #[allow(dead_code)]
     Blue,
 }
 
 impl UiColor {
-    pub fn to_bg_color(&self) -> Color {
+    pub fn to_bg_color(self) -> Color {
         match self {
             UiColor::Grey => Color::from_rgba(140, 140, 140, 200),
             UiColor::Green => Color::from_rgba(16, 60, 50, 200),
@@ -24,7 +22,7 @@ impl UiColor {
         }
     }
 
-    pub fn to_fg_color(&self) -> Color {
+    pub fn to_fg_color(self) -> Color {
         match self {
             UiColor::Grey => Color::from_rgba(255, 255, 255, 200),
             UiColor::Green => Color::from_rgba(255, 255, 255, 200),
@@ -35,7 +33,7 @@ impl UiColor {
         }
     }
 
-    pub fn to_shadow_color(&self) -> Color {
+    pub fn to_shadow_color(self) -> Color {
         let bg_color = self.to_bg_color();
         Color::from_rgba(
             (bg_color.r * 255.) as u8,
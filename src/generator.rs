@@ -1,53 +1,300 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::RangeInclusive, time::Duration};
 
 use crate::{
-    board::{piece::Piece, Board},
-    solver::Solver,
+    board::{Board, errors::SError, piece::Piece, square::Square},
+    difficulty::{self, Difficulty},
+    solver::{self, Solver},
 };
 
+pub mod config;
+pub mod random;
+
+use config::{ConfigError, GeneratorConfig, pool_index};
+
 pub trait RandomRange {
     fn gen_range(&self, min: usize, max: usize) -> usize;
 }
 
-pub fn generate(num_pieces: u32, num_solutions: u32, rand: &impl RandomRange) -> GenerateStats {
-    let candidate_pieces = vec![
-        Piece::Pawn,
-        Piece::Pawn,
-        Piece::Pawn,
-        Piece::Pawn,
-        Piece::Bishop,
-        Piece::Bishop,
-        Piece::Bishop,
-        Piece::Bishop,
-        Piece::Knight,
-        Piece::Knight,
-        Piece::Knight,
-        Piece::Queen,
-        Piece::Rook,
-        Piece::Rook,
-    ];
-
-    if num_pieces > candidate_pieces.len().try_into().unwrap() {
-        panic!(
-            "Number of pieces to place on the board should be <= {}",
-            candidate_pieces.len()
-        );
+pub fn generate(
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    generate_with_config(&GeneratorConfig::new(), num_pieces, num_solutions, rand)
+}
+
+/// Same as `generate`, on a board with `width` files and `height` ranks.
+pub fn generate_with_size(
+    width: usize,
+    height: usize,
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let config = GeneratorConfig::with_size(width, height);
+    generate_with_config(&config, num_pieces, num_solutions, rand)
+}
+
+/// Same as `generate`, on the board and with the pieces given by `config`.
+pub fn generate_with_config(
+    config: &GeneratorConfig,
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    generate_on(config, num_pieces, rand, at_most(num_solutions))
+}
+
+/// How strictly `generate_unique` limits puzzles to a single solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Uniqueness {
+    /// Exactly one sequence of moves wins.
+    Unique,
+
+    /// Every winning sequence is made of the same captures, only played in a
+    /// different order. See `solver::group_equivalent`.
+    EssentiallyUnique,
+}
+
+/// Generates a 4x4 puzzle that has a single solution, as strict as asked for
+/// by `uniqueness`. `GenerateStats::uniqueness` tells which one the puzzle
+/// has, as an essentially unique puzzle can turn out to be unique.
+pub fn generate_unique(
+    num_pieces: u32,
+    uniqueness: Uniqueness,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    generate_unique_with_config(&GeneratorConfig::new(), num_pieces, uniqueness, rand)
+}
+
+/// Same as `generate_unique`, on a board with `width` files and `height`
+/// ranks.
+pub fn generate_unique_with_size(
+    width: usize,
+    height: usize,
+    num_pieces: u32,
+    uniqueness: Uniqueness,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let config = GeneratorConfig::with_size(width, height);
+    generate_unique_with_config(&config, num_pieces, uniqueness, rand)
+}
+
+/// Same as `generate_unique`, on the board and with the pieces given by
+/// `config`.
+pub fn generate_unique_with_config(
+    config: &GeneratorConfig,
+    num_pieces: u32,
+    uniqueness: Uniqueness,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let accept = |board: &Board| match uniqueness_of(board) {
+        Some(Uniqueness::Unique) => true,
+        Some(Uniqueness::EssentiallyUnique) => uniqueness == Uniqueness::EssentiallyUnique,
+        None => false,
+    };
+
+    let mut stats = generate_on(config, num_pieces, rand, accept)?;
+    stats.uniqueness = stats.board.as_ref().and_then(uniqueness_of);
+    Ok(stats)
+}
+
+fn uniqueness_of(board: &Board) -> Option<Uniqueness> {
+    let solver = Solver::new(board.clone());
+    match solver.solutions_up_to(2).len() {
+        0 => return None,
+        1 => return Some(Uniqueness::Unique),
+        _ => {}
+    }
+
+    if solver::group_equivalent(solver.solve()).len() == 1 {
+        Some(Uniqueness::EssentiallyUnique)
+    } else {
+        None
+    }
+}
+
+fn at_most(num_solutions: u32) -> impl Fn(&Board) -> bool {
+    move |board| {
+        let limit = (num_solutions as usize).saturating_add(1);
+        Solver::new(board.clone()).solutions_up_to(limit).len() <= num_solutions as usize
     }
+}
 
+fn generate_on(
+    config: &GeneratorConfig,
+    num_pieces: u32,
+    rand: &impl RandomRange,
+    accept: impl Fn(&Board) -> bool,
+) -> Result<GenerateStats, SError> {
+    let board = empty_board(config, num_pieces)?;
     let attempts: u32 = 1000;
     let mut overall_stats = GenerateStats::new(0, 0, 0, None);
     for _ in 0..attempts {
-        let stats = try_generate(num_pieces, num_solutions, rand, candidate_pieces.clone());
-        overall_stats.piece_total += stats.piece_total;
-        overall_stats.piece_success += stats.piece_success;
-        overall_stats.total += stats.total;
+        let stats = try_generate(board.clone(), num_pieces, rand, config, &accept);
+        overall_stats.add(&stats);
+        overall_stats.board = stats.board;
+        if overall_stats.board.is_some() {
+            return Ok(overall_stats);
+        }
+    }
+
+    Ok(overall_stats)
+}
+
+/// Generates a 4x4 puzzle backwards, starting from the last piece left on the
+/// board. Pieces are added by taking captures back: a piece steps back to a
+/// square it could have captured from and leaves the captured piece behind.
+/// Playing the captures forward again always wins, so unlike `generate` the
+/// solver is never needed to find out whether the puzzle can be solved. It is
+/// only asked whether the puzzle has at most `num_solutions` solutions.
+pub fn generate_reverse(
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    generate_reverse_with_config(&GeneratorConfig::new(), num_pieces, num_solutions, rand)
+}
+
+/// Same as `generate_reverse`, on a board with `width` files and `height`
+/// ranks.
+pub fn generate_reverse_with_size(
+    width: usize,
+    height: usize,
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let config = GeneratorConfig::with_size(width, height);
+    generate_reverse_with_config(&config, num_pieces, num_solutions, rand)
+}
+
+/// Same as `generate_reverse`, on the board and with the pieces given by
+/// `config`.
+pub fn generate_reverse_with_config(
+    config: &GeneratorConfig,
+    num_pieces: u32,
+    num_solutions: u32,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let board = empty_board(config, num_pieces)?;
+    let accept = at_most(num_solutions);
+    // Attempts are cheap, the solver only runs once each, but more of them
+    // end up with too many solutions
+    let attempts: u32 = 5000;
+    let mut overall_stats = GenerateStats::new(0, 0, 0, None);
+    for _ in 0..attempts {
+        let stats = try_generate_reverse(board.clone(), num_pieces, rand, config, &accept);
+        overall_stats.add(&stats);
         overall_stats.board = stats.board;
         if overall_stats.board.is_some() {
-            return overall_stats;
+            return Ok(overall_stats);
         }
     }
 
-    overall_stats
+    Ok(overall_stats)
+}
+
+fn empty_board(config: &GeneratorConfig, num_pieces: u32) -> Result<Board, SError> {
+    config.validate(num_pieces)?;
+    Board::with_size(config.width, config.height)
+}
+
+/// The kind of puzzle `generate_with_difficulty` looks for.
+pub struct DifficultyTarget {
+    /// Acceptable ratings, as given by `difficulty::rate`.
+    pub rating: RangeInclusive<f64>,
+
+    /// Number of pieces to place on the board. When not set, pieces are added
+    /// or taken away until puzzles land in the rating range.
+    pub num_pieces: Option<u32>,
+
+    /// Give up after this long. There is no clock on wasm, so there only the
+    /// number of attempts is limited.
+    pub time_budget: Option<Duration>,
+
+    /// The board and the pieces to build puzzles from. Ratings are tuned for
+    /// the default 4x4 board.
+    pub config: GeneratorConfig,
+}
+
+impl DifficultyTarget {
+    pub fn new(rating: RangeInclusive<f64>) -> Self {
+        DifficultyTarget {
+            rating,
+            num_pieces: None,
+            time_budget: None,
+            config: GeneratorConfig::new(),
+        }
+    }
+}
+
+/// Generates puzzles until one is rated within `target.rating`. The rating is
+/// available from `GenerateStats::difficulty`.
+pub fn generate_with_difficulty(
+    target: &DifficultyTarget,
+    rand: &impl RandomRange,
+) -> Result<GenerateStats, SError> {
+    let config = &target.config;
+    let min_pieces = u32::max(2, config.min_pieces());
+    let max_pieces = config.max_pieces();
+    let board = empty_board(config, target.num_pieces.unwrap_or(min_pieces))?;
+    if target.num_pieces.is_some_and(|n| n < min_pieces) {
+        return Err(ConfigError::TooManyRequired {
+            required: min_pieces,
+        }
+        .into());
+    }
+
+    let out_of_time = deadline(target.time_budget);
+    let mut num_pieces = target
+        .num_pieces
+        .unwrap_or_else(|| 5.clamp(min_pieces, u32::max(min_pieces, max_pieces)));
+    let attempts: u32 = 1000;
+    let mut overall_stats = GenerateStats::new(0, 0, 0, None);
+    for _ in 0..attempts {
+        if out_of_time() {
+            break;
+        }
+
+        let stats = try_generate(board.clone(), num_pieces, rand, config, |_: &Board| true);
+        overall_stats.add(&stats);
+        let Some(board) = stats.board else {
+            continue;
+        };
+
+        let Some(difficulty) = difficulty::rate(&board) else {
+            continue;
+        };
+
+        if target.rating.contains(&difficulty.rating) {
+            overall_stats.board = Some(board);
+            overall_stats.difficulty = Some(difficulty);
+            return Ok(overall_stats);
+        }
+
+        if target.num_pieces.is_none() {
+            if difficulty.rating < *target.rating.start() {
+                num_pieces = u32::min(num_pieces + 1, max_pieces);
+            } else {
+                num_pieces = u32::max(num_pieces - 1, min_pieces);
+            }
+        }
+    }
+
+    Ok(overall_stats)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn deadline(budget: Option<Duration>) -> impl Fn() -> bool {
+    let start = std::time::Instant::now();
+    move || budget.is_some_and(|budget| start.elapsed() >= budget)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn deadline(_budget: Option<Duration>) -> impl Fn() -> bool {
+    || false
 }
 
 pub struct GenerateStats {
@@ -55,6 +302,8 @@ pub struct GenerateStats {
     piece_success: u32,
     total: u32,
     board: Option<Board>,
+    difficulty: Option<Difficulty>,
+    uniqueness: Option<Uniqueness>,
 }
 
 impl GenerateStats {
@@ -64,21 +313,77 @@ impl GenerateStats {
             piece_success,
             total,
             board,
+            difficulty: None,
+            uniqueness: None,
         }
     }
 
+    fn add(&mut self, other: &GenerateStats) {
+        self.piece_total += other.piece_total;
+        self.piece_success += other.piece_success;
+        self.total += other.total;
+    }
+
     pub fn print_stats(&self) {
         let mut stats = String::new();
         add_stat(&mut stats, "Total attempts", self.total);
         add_stat(&mut stats, "Total pieces placed", self.piece_total);
         add_stat(&mut stats, "Success pieces placed", self.piece_success);
+        if let Some(difficulty) = &self.difficulty {
+            add_stat(
+                &mut stats,
+                "Difficulty",
+                format!("{:.2}", difficulty.rating),
+            );
+        }
+        if let Some(uniqueness) = self.uniqueness {
+            add_stat(&mut stats, "Uniqueness", uniqueness);
+        }
 
         println!("{}", stats);
     }
 
+    /// Number of boards that were started on, one for every time the
+    /// generator gave up on a board and began again.
+    pub fn attempts(&self) -> u32 {
+        self.total
+    }
+
+    /// Number of pieces put down on a board, including the ones taken back
+    /// because they left the board with no solution.
+    pub fn pieces_placed(&self) -> u32 {
+        self.piece_total
+    }
+
+    /// Number of pieces put down that were kept.
+    pub fn pieces_kept(&self) -> u32 {
+        self.piece_success
+    }
+
     pub fn board(self) -> Option<Board> {
         self.board
     }
+
+    /// The rating of the generated board, when it was generated to a
+    /// difficulty.
+    pub fn difficulty(&self) -> Option<&Difficulty> {
+        self.difficulty.as_ref()
+    }
+
+    /// How unique the solution of the generated board is, when it was
+    /// generated to have a single solution.
+    pub fn uniqueness(&self) -> Option<Uniqueness> {
+        self.uniqueness
+    }
+}
+
+impl Display for Uniqueness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Uniqueness::Unique => write!(f, "Unique"),
+            Uniqueness::EssentiallyUnique => write!(f, "Essentially unique"),
+        }
+    }
 }
 
 fn add_stat<T>(stats: &mut String, name: &str, val: T)
@@ -89,19 +394,21 @@ where
 }
 
 fn try_generate(
+    mut board: Board,
     num_pieces: u32,
-    num_solutions: u32,
     rand: &impl RandomRange,
-    mut candidate_pieces: Vec<Piece>,
+    config: &GeneratorConfig,
+    accept: impl Fn(&Board) -> bool,
 ) -> GenerateStats {
-    let mut board = Board::new();
+    let mut pool = config.pool();
+    let mut placed = [0; 6];
     let mut piece_total = 0;
     let mut piece_success = 0;
-    for _ in 0..num_pieces {
-        let mut placed = false;
+    for slots in (1..=num_pieces).rev() {
+        let mut placed_piece = false;
         let empty_squares = board.empty_squares();
         let mut attempts = 15;
-        while !placed {
+        while !placed_piece {
             if attempts == 0 {
                 return GenerateStats::new(piece_total, piece_success, 1, None);
             }
@@ -109,17 +416,18 @@ fn try_generate(
             attempts -= 1;
             piece_total += 1;
 
-            let index = rand.gen_range(0, candidate_pieces.len());
-            let piece = candidate_pieces[index];
+            let Some(piece) = config.pick(&pool, &placed, slots, rand) else {
+                return GenerateStats::new(piece_total, piece_success, 1, None);
+            };
             let square_index = rand.gen_range(0, empty_squares.len());
             let mut random_square = empty_squares[square_index].clone();
             random_square.piece = Some(piece);
             board.set(random_square.clone());
-            let solutions = Solver::new(board.clone()).solve();
-            if solutions.len() > 0 {
-                placed = true;
+            if Solver::new(board.clone()).is_solvable() {
+                placed_piece = true;
                 piece_success += 1;
-                candidate_pieces.remove(index);
+                pool[pool_index(piece)] -= 1;
+                placed[pool_index(piece)] += 1;
                 continue;
             }
 
@@ -128,19 +436,118 @@ fn try_generate(
         }
     }
 
-    let solutions = Solver::new(board.clone()).solve();
-    if solutions.len() > num_solutions as usize {
-        GenerateStats::new(piece_total, piece_success, 1, None)
-    } else {
+    if accept(&board) {
         GenerateStats::new(piece_total, piece_success, 1, Some(board))
+    } else {
+        GenerateStats::new(piece_total, piece_success, 1, None)
     }
 }
 
+fn try_generate_reverse(
+    mut board: Board,
+    num_pieces: u32,
+    rand: &impl RandomRange,
+    config: &GeneratorConfig,
+    accept: impl Fn(&Board) -> bool,
+) -> GenerateStats {
+    if num_pieces == 0 {
+        return GenerateStats::new(0, 0, 1, None);
+    }
+
+    let mut pool = config.pool();
+    let mut placed = [0; 6];
+    let empty_squares = board.empty_squares();
+    let mut last = empty_squares[rand.gen_range(0, empty_squares.len())].clone();
+    let Some(piece) = config.pick(&pool, &placed, num_pieces, rand) else {
+        return GenerateStats::new(1, 0, 1, None);
+    };
+    pool[pool_index(piece)] -= 1;
+    placed[pool_index(piece)] += 1;
+    last.piece = Some(piece);
+    board.set(last);
+    let mut piece_total = 1;
+    let mut piece_success = 1;
+
+    for slots in (1..num_pieces).rev() {
+        let uncaptures = uncaptures(&board);
+        piece_total += 1;
+        if uncaptures.is_empty() {
+            return GenerateStats::new(piece_total, piece_success, 1, None);
+        }
+
+        let Some(captured) = config.pick(&pool, &placed, slots, rand) else {
+            return GenerateStats::new(piece_total, piece_success, 1, None);
+        };
+
+        // Positions with fewer captures to choose from tend to end up with
+        // fewer solutions, and counting them doesn't need the solver
+        let branching: Vec<usize> = uncaptures
+            .iter()
+            .map(|(capture_square, from)| branching(&mut board, capture_square, from, captured))
+            .collect();
+        let fewest = branching.iter().min().copied().unwrap_or(0);
+        let candidates: Vec<&(Square, Square)> = uncaptures
+            .iter()
+            .zip(&branching)
+            .filter(|(_, moves)| **moves == fewest)
+            .map(|(uncapture, _)| uncapture)
+            .collect();
+        let (capture_square, from) = candidates[rand.gen_range(0, candidates.len())];
+        piece_success += 1;
+        pool[pool_index(captured)] -= 1;
+        placed[pool_index(captured)] += 1;
+        board.set(board.square(from.file, from.rank, capture_square.piece));
+        board.set(board.square(capture_square.file, capture_square.rank, Some(captured)));
+    }
+
+    // Taking captures back always leaves a puzzle that can be won, so the
+    // solver is only needed once to count the solutions
+    let board = accept(&board).then_some(board);
+    GenerateStats::new(piece_total, piece_success, 1, board)
+}
+
+/// The number of legal moves after taking back the capture on
+/// `capture_square` from `from`, leaving `captured` behind. `board` is put
+/// back as it was.
+fn branching(board: &mut Board, capture_square: &Square, from: &Square, captured: Piece) -> usize {
+    board.set(board.square(from.file, from.rank, capture_square.piece));
+    board.set(board.square(capture_square.file, capture_square.rank, Some(captured)));
+    let moves = board.legal_moves.len();
+    board.set(board.square(from.file, from.rank, None));
+    board.set(capture_square.clone());
+    moves
+}
+
+/// Captures that can be taken back on `board`, as the square the capture
+/// ended on and the empty square the capturing piece came from.
+fn uncaptures(board: &Board) -> Vec<(Square, Square)> {
+    let mut uncaptures = Vec::new();
+    for file in 0..board.width() {
+        for rank in 0..board.height() {
+            let Some(piece) = board.cells[file][rank] else {
+                continue;
+            };
+
+            let capture_square = board.square(file, rank, Some(piece));
+            for from in board.capture_origins(piece, &capture_square) {
+                uncaptures.push((capture_square.clone(), from));
+            }
+        }
+    }
+
+    uncaptures
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{board::BoardState, solver::Solver};
+    use crate::{
+        board::{BoardState, piece::Piece},
+        solver::Solver,
+    };
+
+    use super::{random::SeededRandom, *};
 
-    use super::*;
+    use std::error::Error;
 
     use rand::Rng;
 
@@ -154,13 +561,209 @@ mod tests {
     #[test]
     fn generator_smoke() {
         for _ in 0..10 {
-            let gen_stats = generate(5, 5, &TestRandom);
+            let gen_stats = generate(5, 5, &TestRandom).unwrap();
+            let board = gen_stats.board.expect("No puzzle was generated");
+            assert_eq!(board.game_state, BoardState::InProgress);
+
+            let solutions = Solver::new(board).solve();
+            assert!(solutions.len() <= 5);
+            assert!(!solutions.is_empty());
+        }
+    }
+
+    #[test]
+    fn generator_smoke_sized() {
+        for (width, height) in [(3, 3), (5, 5), (6, 4)] {
+            let gen_stats = generate_with_size(width, height, 5, 5, &TestRandom).unwrap();
             let board = gen_stats.board.expect("No puzzle was generated");
+            assert_eq!(board.width(), width);
+            assert_eq!(board.height(), height);
             assert_eq!(board.game_state, BoardState::InProgress);
 
             let solutions = Solver::new(board).solve();
             assert!(solutions.len() <= 5);
-            assert!(solutions.len() >= 1);
+            assert!(!solutions.is_empty());
         }
     }
+
+    #[test]
+    fn generator_difficulty() {
+        let target = DifficultyTarget::new(4.0..=8.0);
+        for _ in 0..5 {
+            let gen_stats = generate_with_difficulty(&target, &TestRandom).unwrap();
+            let rating = gen_stats.difficulty().expect("No rating").rating;
+            assert!(target.rating.contains(&rating));
+
+            let board = gen_stats.board.expect("No puzzle was generated");
+            assert_eq!(Some(rating), difficulty::rate(&board).map(|d| d.rating));
+        }
+
+        let mut target = DifficultyTarget::new(0.0..=100.0);
+        target.num_pieces = Some(6);
+        let board = generate_with_difficulty(&target, &TestRandom)
+            .unwrap()
+            .board()
+            .expect("No puzzle was generated");
+        assert_eq!(6, board.cells.iter().flatten().flatten().count());
+
+        for num_pieces in [1, 20] {
+            target.num_pieces = Some(num_pieces);
+            let Err(error) = generate_with_difficulty(&target, &TestRandom) else {
+                panic!("{} pieces should not fit the config", num_pieces);
+            };
+            assert!(matches!(error, SError::InvalidConfig(_)));
+            assert!(error.source().is_some());
+        }
+    }
+
+    #[test]
+    fn generator_seeded() {
+        let generate = |seed| {
+            generate(6, 10, &SeededRandom::new(seed))
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated")
+                .id()
+        };
+
+        assert_eq!(generate(7), generate(7));
+        assert_eq!(generate(2024), generate(2024));
+        assert_ne!(generate(7), generate(2024));
+
+        let target = DifficultyTarget::new(4.0..=8.0);
+        let generate = |seed| {
+            generate_with_difficulty(&target, &SeededRandom::new(seed))
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated")
+                .id()
+        };
+        assert_eq!(generate(99), generate(99));
+    }
+
+    #[test]
+    fn generator_unique() {
+        for _ in 0..5 {
+            let gen_stats = generate_unique(5, Uniqueness::Unique, &TestRandom).unwrap();
+            assert_eq!(Some(Uniqueness::Unique), gen_stats.uniqueness());
+
+            let board = gen_stats.board.expect("No puzzle was generated");
+            assert_eq!(1, Solver::new(board).solve().len());
+        }
+
+        for _ in 0..5 {
+            let gen_stats = generate_unique(6, Uniqueness::EssentiallyUnique, &TestRandom).unwrap();
+            let uniqueness = gen_stats.uniqueness().expect("No uniqueness reported");
+
+            let board = gen_stats.board.expect("No puzzle was generated");
+            let solutions = Solver::new(board).solve();
+            assert_eq!(1, solver::group_equivalent(solutions.clone()).len());
+            assert_eq!(uniqueness == Uniqueness::Unique, solutions.len() == 1);
+        }
+    }
+
+    #[test]
+    fn generator_reverse() {
+        for (width, height, num_pieces) in [(4, 4, 6), (3, 3, 4), (6, 5, 7)] {
+            for num_solutions in [1, 5] {
+                let gen_stats = generate_reverse_with_size(
+                    width,
+                    height,
+                    num_pieces,
+                    num_solutions,
+                    &TestRandom,
+                )
+                .unwrap();
+                let board = gen_stats.board.expect("No puzzle was generated");
+                assert_eq!(
+                    num_pieces as usize,
+                    board.cells.iter().flatten().flatten().count()
+                );
+                assert_eq!(board.game_state, BoardState::InProgress);
+
+                let solutions = Solver::new(board.clone()).solve();
+                assert!(!solutions.is_empty());
+                assert!(solutions.len() <= num_solutions as usize);
+
+                // Never more of a piece than the pool has
+                let count = |piece| {
+                    board
+                        .cells
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .filter(|p| **p == piece)
+                        .count()
+                };
+                assert!(count(Piece::Queen) <= 1);
+                assert!(count(Piece::Rook) <= 2);
+                assert!(count(Piece::Knight) <= 3);
+                assert!(count(Piece::King) == 0);
+            }
+        }
+
+        let seeded = |seed| {
+            generate_reverse(7, 3, &SeededRandom::new(seed))
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated")
+                .id()
+        };
+        assert_eq!(seeded(11), seeded(11));
+    }
+
+    #[test]
+    fn generator_config() {
+        let count = |board: &Board, piece| {
+            board
+                .cells
+                .iter()
+                .flatten()
+                .flatten()
+                .filter(|p| **p == piece)
+                .count() as u32
+        };
+
+        // Must contain a king, and never more than one knight
+        let mut config = GeneratorConfig::new();
+        config.require(Piece::King, 1);
+        config.knight.max = 1;
+        config.pawn.weight = 3;
+        for _ in 0..5 {
+            let board = generate_with_config(&config, 6, 5, &TestRandom)
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated");
+            assert_eq!(1, count(&board, Piece::King));
+            assert!(count(&board, Piece::Knight) <= 1);
+
+            let board = generate_reverse_with_config(&config, 6, 5, &TestRandom)
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated");
+            assert_eq!(1, count(&board, Piece::King));
+            assert!(count(&board, Piece::Knight) <= 1);
+        }
+
+        // Only rooks and bishops on a 5x5 board, with at least 2 rooks
+        let config = GeneratorConfig::parse("size 5x5\nR 3 2\nB 4").unwrap();
+        let board =
+            generate_unique_with_config(&config, 4, Uniqueness::EssentiallyUnique, &TestRandom)
+                .unwrap()
+                .board()
+                .expect("No puzzle was generated");
+        assert_eq!(5, board.width());
+        assert!(count(&board, Piece::Rook) >= 2);
+        assert_eq!(4, count(&board, Piece::Rook) + count(&board, Piece::Bishop));
+
+        let mut target = DifficultyTarget::new(0.0..=100.0);
+        target.config.require(Piece::Queen, 1);
+        target.config.require(Piece::King, 1);
+        let board = generate_with_difficulty(&target, &TestRandom)
+            .unwrap()
+            .board()
+            .expect("No puzzle was generated");
+        assert_eq!(1, count(&board, Piece::Queen));
+        assert_eq!(1, count(&board, Piece::King));
+    }
 }
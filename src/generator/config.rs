@@ -0,0 +1,472 @@
+use std::fmt::{self, Display, Formatter};
+
+pub use crate::board::errors::ConfigError;
+use crate::board::{
+    constants::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    piece::Piece,
+};
+
+// The order pieces are drawn from the pool in. Keeps seeded puzzles the same
+// as when the pool was a fixed list of pieces.
+const POOL_ORDER: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::King,
+];
+
+/// The highest `PieceLimits::weight` a config can give a piece.
+pub const MAX_WEIGHT: u32 = 1000;
+
+/// How many of one kind of piece the generator can place, and how often.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PieceLimits {
+    /// Most pieces of this kind on the board.
+    pub max: u32,
+
+    /// Fewest pieces of this kind on the board. Set this to require a piece.
+    pub min: u32,
+
+    /// How likely each one of these pieces is to be picked, compared to the
+    /// other pieces left in the pool. With a weight of 0 the piece is only
+    /// placed to make up `min`.
+    pub weight: u32,
+}
+
+/// The board and the pool of pieces the generator builds puzzles from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    pub width: usize,
+    pub height: usize,
+    pub king: PieceLimits,
+    pub queen: PieceLimits,
+    pub rook: PieceLimits,
+    pub bishop: PieceLimits,
+    pub knight: PieceLimits,
+    pub pawn: PieceLimits,
+}
+
+impl PieceLimits {
+    pub fn new(max: u32) -> Self {
+        PieceLimits {
+            max,
+            min: 0,
+            weight: 1,
+        }
+    }
+
+    /// No pieces of this kind.
+    pub fn none() -> Self {
+        PieceLimits::new(0)
+    }
+}
+
+impl GeneratorConfig {
+    /// A 4x4 board with 4 pawns, 4 bishops, 3 knights, 2 rooks and a queen,
+    /// all equally likely to be picked.
+    pub fn new() -> Self {
+        GeneratorConfig::with_size(4, 4)
+    }
+
+    /// Same pool of pieces as `new`, on a board with `width` files and
+    /// `height` ranks.
+    pub fn with_size(width: usize, height: usize) -> Self {
+        GeneratorConfig {
+            width,
+            height,
+            king: PieceLimits::none(),
+            queen: PieceLimits::new(1),
+            rook: PieceLimits::new(2),
+            bishop: PieceLimits::new(4),
+            knight: PieceLimits::new(3),
+            pawn: PieceLimits::new(4),
+        }
+    }
+
+    pub fn limits(&self, piece: Piece) -> &PieceLimits {
+        match piece {
+            Piece::King => &self.king,
+            Piece::Queen => &self.queen,
+            Piece::Rook => &self.rook,
+            Piece::Bishop => &self.bishop,
+            Piece::Knight => &self.knight,
+            Piece::Pawn => &self.pawn,
+        }
+    }
+
+    pub fn limits_mut(&mut self, piece: Piece) -> &mut PieceLimits {
+        match piece {
+            Piece::King => &mut self.king,
+            Piece::Queen => &mut self.queen,
+            Piece::Rook => &mut self.rook,
+            Piece::Bishop => &mut self.bishop,
+            Piece::Knight => &mut self.knight,
+            Piece::Pawn => &mut self.pawn,
+        }
+    }
+
+    /// Makes every puzzle have at least `count` pieces of this kind, adding
+    /// them to the pool if needed.
+    pub fn require(&mut self, piece: Piece, count: u32) {
+        let limits = self.limits_mut(piece);
+        limits.min = count;
+        limits.max = u32::max(limits.max, count);
+    }
+
+    /// Checks that puzzles with `num_pieces` pieces can be built from this
+    /// config.
+    pub fn validate(&self, num_pieces: u32) -> Result<(), ConfigError> {
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&self.width)
+            || !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&self.height)
+        {
+            return Err(ConfigError::InvalidDimensions {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        for piece in POOL_ORDER {
+            let limits = self.limits(piece);
+            if limits.min > limits.max {
+                return Err(ConfigError::MinAboveMax(piece));
+            }
+            if limits.weight > MAX_WEIGHT {
+                return Err(ConfigError::WeightTooHigh {
+                    piece,
+                    max: MAX_WEIGHT,
+                });
+            }
+        }
+
+        let required = self.min_pieces();
+        if required > num_pieces {
+            return Err(ConfigError::TooManyRequired { required });
+        }
+
+        let available = self.max_pieces();
+        if num_pieces > available {
+            return Err(ConfigError::TooManyPieces { available });
+        }
+
+        Ok(())
+    }
+
+    /// The most pieces a puzzle can be built with.
+    pub fn max_pieces(&self) -> u32 {
+        let available = POOL_ORDER
+            .iter()
+            .map(|p| self.limits(*p))
+            .map(|l| if l.weight > 0 { l.max } else { l.min })
+            .fold(0, u32::saturating_add);
+        u32::min(available, (self.width * self.height) as u32)
+    }
+
+    /// The fewest pieces a puzzle can be built with.
+    pub fn min_pieces(&self) -> u32 {
+        POOL_ORDER
+            .iter()
+            .map(|p| self.limits(*p).min)
+            .fold(0, u32::saturating_add)
+    }
+
+    /// Reads a config written like below. Pieces that are not listed are
+    /// never placed. `min` and `weight` can be left out, and default to 0
+    /// and 1. `max` and `min` can't be more than the squares on the largest
+    /// board, and `weight` can't be more than `MAX_WEIGHT`.
+    ///
+    /// ```text
+    /// # Comments start with a '#'
+    /// size 5x4
+    /// # piece max min weight
+    /// K 1 1
+    /// N 3 0 2
+    /// P 4
+    /// ```
+    pub fn parse(config: &str) -> Result<Self, ConfigError> {
+        let mut parsed = GeneratorConfig::with_size(4, 4);
+        for piece in POOL_ORDER {
+            *parsed.limits_mut(piece) = PieceLimits::none();
+        }
+
+        for (index, line) in config.lines().enumerate() {
+            let invalid = ConfigError::InvalidLine(index + 1);
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(first) = words.next() else {
+                continue;
+            };
+
+            let numbers: Vec<&str> = words.collect();
+            if first == "size" {
+                let [size] = numbers[..] else {
+                    return Err(invalid);
+                };
+                let Some((width, height)) = size.split_once('x') else {
+                    return Err(invalid);
+                };
+                parsed.width = width.parse().map_err(|_| invalid.clone())?;
+                parsed.height = height.parse().map_err(|_| invalid)?;
+                continue;
+            }
+
+            let Ok(Some(piece)) = Piece::parse(first) else {
+                return Err(invalid);
+            };
+            if numbers.is_empty() || numbers.len() > 3 {
+                return Err(invalid);
+            }
+
+            let mut values = [0, 0, 1];
+            for (value, number) in values.iter_mut().zip(numbers) {
+                *value = number.parse().map_err(|_| invalid.clone())?;
+            }
+
+            let [max, min, weight] = values;
+            let max_squares = (MAX_BOARD_SIZE * MAX_BOARD_SIZE) as u32;
+            if max > max_squares || min > max_squares || weight > MAX_WEIGHT {
+                return Err(invalid);
+            }
+            *parsed.limits_mut(piece) = PieceLimits { max, min, weight };
+        }
+
+        Ok(parsed)
+    }
+
+    /// Picks a piece from `pool`, which holds how many of each piece are left
+    /// to place, indexed like `POOL_ORDER`. Pieces below their `min` are the
+    /// only ones picked once there are just enough `slots` left for them.
+    pub(super) fn pick(
+        &self,
+        pool: &[u32; 6],
+        placed: &[u32; 6],
+        slots: u32,
+        rand: &impl super::RandomRange,
+    ) -> Option<Piece> {
+        let missing = POOL_ORDER
+            .iter()
+            .enumerate()
+            .map(|(i, p)| self.limits(*p).min.saturating_sub(placed[i]))
+            .fold(0, u32::saturating_add);
+        let only_missing = missing >= slots;
+
+        let weights: Vec<u64> = POOL_ORDER
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let limits = self.limits(*p);
+                if only_missing {
+                    let missing = limits.min.saturating_sub(placed[i]);
+                    u32::min(missing, pool[i]) as u64
+                } else {
+                    (pool[i] as u64).saturating_mul(limits.weight as u64)
+                }
+            })
+            .collect();
+
+        let total = weights.iter().copied().fold(0, u64::saturating_add);
+        if total == 0 {
+            return None;
+        }
+
+        let total = usize::try_from(total).unwrap_or(usize::MAX);
+        let mut pick = rand.gen_range(0, total) as u64;
+        for (piece, weight) in POOL_ORDER.iter().zip(weights) {
+            if pick < weight {
+                return Some(*piece);
+            }
+            pick -= weight;
+        }
+
+        None
+    }
+
+    /// How many of each piece can be placed, indexed like `POOL_ORDER`.
+    pub(super) fn pool(&self) -> [u32; 6] {
+        POOL_ORDER.map(|p| self.limits(p).max)
+    }
+}
+
+/// Where `piece` is counted in the arrays passed to `GeneratorConfig::pick`.
+pub(super) fn pool_index(piece: Piece) -> usize {
+    match piece {
+        Piece::Pawn => 0,
+        Piece::Bishop => 1,
+        Piece::Knight => 2,
+        Piece::Queen => 3,
+        Piece::Rook => 4,
+        Piece::King => 5,
+    }
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig::new()
+    }
+}
+
+impl Display for GeneratorConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size {}x{}", self.width, self.height)?;
+        writeln!(f, "# piece max min weight")?;
+        for piece in POOL_ORDER {
+            let limits = self.limits(piece);
+            if limits.max > 0 {
+                writeln!(
+                    f,
+                    "{} {} {} {}",
+                    piece.notation(),
+                    limits.max,
+                    limits.min,
+                    limits.weight
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_validate() {
+        let mut config = GeneratorConfig::new();
+        assert_eq!(Ok(()), config.validate(14));
+        assert_eq!(
+            Err(ConfigError::TooManyPieces { available: 14 }),
+            config.validate(15)
+        );
+
+        config.require(Piece::King, 1);
+        assert_eq!(Ok(()), config.validate(15));
+        assert_eq!(
+            Err(ConfigError::TooManyPieces { available: 15 }),
+            config.validate(16)
+        );
+
+        config.require(Piece::Pawn, 3);
+        config.require(Piece::Queen, 1);
+        assert_eq!(
+            Err(ConfigError::TooManyRequired { required: 5 }),
+            config.validate(4)
+        );
+
+        config.rook.min = 3;
+        assert_eq!(
+            Err(ConfigError::MinAboveMax(Piece::Rook)),
+            config.validate(8)
+        );
+
+        assert_eq!(
+            Err(ConfigError::InvalidDimensions {
+                width: 9,
+                height: 4
+            }),
+            GeneratorConfig::with_size(9, 4).validate(3)
+        );
+
+        let mut config = GeneratorConfig::new();
+        config.knight.weight = MAX_WEIGHT + 1;
+        assert_eq!(
+            Err(ConfigError::WeightTooHigh {
+                piece: Piece::Knight,
+                max: MAX_WEIGHT
+            }),
+            config.validate(3)
+        );
+
+        let mut config = GeneratorConfig::new();
+        config.king = PieceLimits::new(u32::MAX);
+        config.queen = PieceLimits::new(u32::MAX);
+        config.king.min = u32::MAX;
+        config.queen.min = u32::MAX;
+        assert_eq!(u32::MAX, config.min_pieces());
+        assert_eq!(16, config.max_pieces());
+        assert_eq!(
+            Err(ConfigError::TooManyRequired { required: u32::MAX }),
+            config.validate(3)
+        );
+
+        for (index, piece) in POOL_ORDER.iter().enumerate() {
+            assert_eq!(index, pool_index(*piece));
+        }
+    }
+
+    #[test]
+    fn config_pick_large_weights() {
+        let mut config = GeneratorConfig::new();
+        for piece in POOL_ORDER {
+            config.limits_mut(piece).weight = u32::MAX;
+        }
+
+        let rand = super::super::random::SeededRandom::new(1);
+        let pool = [u32::MAX; 6];
+        assert!(config.pick(&pool, &[0; 6], 5, &rand).is_some());
+    }
+
+    #[test]
+    fn config_parse() {
+        let config = GeneratorConfig::parse(
+            "# A config\n\
+             size 5x4\n\
+             K 1 1 # must have a king\n\
+             N 3 0 2\n\
+             P 4\n",
+        )
+        .expect("config should parse");
+        assert_eq!(5, config.width);
+        assert_eq!(4, config.height);
+        assert_eq!(
+            PieceLimits {
+                max: 1,
+                min: 1,
+                weight: 1
+            },
+            config.king
+        );
+        assert_eq!(
+            PieceLimits {
+                max: 3,
+                min: 0,
+                weight: 2
+            },
+            config.knight
+        );
+        assert_eq!(PieceLimits::new(4), config.pawn);
+        assert_eq!(PieceLimits::none(), config.queen);
+
+        assert_eq!(
+            Ok(config.clone()),
+            GeneratorConfig::parse(&config.to_string())
+        );
+        assert_eq!(
+            Ok(GeneratorConfig::new()),
+            GeneratorConfig::parse(&GeneratorConfig::new().to_string())
+        );
+
+        assert_eq!(
+            Err(ConfigError::InvalidLine(2)),
+            GeneratorConfig::parse("P 4\nX 1")
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidLine(1)),
+            GeneratorConfig::parse("size 4")
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidLine(1)),
+            GeneratorConfig::parse("Q 1 0 1 1")
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidLine(1)),
+            GeneratorConfig::parse("K 4000000000 4000000000")
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidLine(1)),
+            GeneratorConfig::parse("N 3 0 4000000000")
+        );
+    }
+}
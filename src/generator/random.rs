@@ -0,0 +1,62 @@
+use std::cell::Cell;
+
+use super::RandomRange;
+
+/// A small random number generator (SplitMix64) that only needs a seed. It
+/// gives the same numbers for the same seed on every platform, wasm included,
+/// so a seed and the generator's parameters are enough to share a puzzle.
+pub struct SeededRandom {
+    state: Cell<u64>,
+}
+
+impl SeededRandom {
+    pub fn new(seed: u64) -> Self {
+        SeededRandom {
+            state: Cell::new(seed),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e3779b97f4a7c15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl RandomRange for SeededRandom {
+    /// A number in `min..max`, or `min` when the range is empty.
+    fn gen_range(&self, min: usize, max: usize) -> usize {
+        if max <= min {
+            return min;
+        }
+
+        // Work in u64 so that 32 bit targets pick the same numbers
+        let range = (max - min) as u64;
+        min + (self.next_u64() % range) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_random_is_stable() {
+        // Reference values for SplitMix64 seeded with 0
+        let rand = SeededRandom::new(0);
+        assert_eq!(0xe220a8397b1dcdaf, rand.next_u64());
+        assert_eq!(0x6e789e6aa1b965f4, rand.next_u64());
+        assert_eq!(0x06c45d188009454f, rand.next_u64());
+
+        let rand = SeededRandom::new(42);
+        for _ in 0..1000 {
+            let n = rand.gen_range(3, 7);
+            assert!((3..7).contains(&n));
+        }
+        assert_eq!(3, rand.gen_range(3, 3));
+    }
+}
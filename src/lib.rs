@@ -1,3 +1,6 @@
 pub mod board;
+pub mod daily;
+pub mod difficulty;
 pub mod generator;
+pub mod pack;
 pub mod solver;
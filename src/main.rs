@@ -1,6 +1,7 @@
-use game::{sound::Sounds, Game};
+use game::{Game, sound::Sounds};
 use macroquad::{audio, prelude::*};
 use miniquad::date;
+use sol_chess::pack::Pack;
 
 mod game;
 
@@ -48,7 +49,33 @@ async fn init() -> Game {
     let loss = load_sound!("../assets/loss.wav");
     let button = load_sound!("../assets/button.wav");
     let mode = load_sound!("../assets/mode.wav");
-    let sounds = Sounds { click, win, loss, button, mode };
-    let game = Game::new(texture_res, sounds);
-    game
+    let sounds = Sounds {
+        click,
+        win,
+        loss,
+        button,
+        mode,
+    };
+    Game::new(texture_res, sounds, load_pack())
+}
+
+/// The puzzle pack named on the command line, like `sol_chess pack.txt`.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_pack() -> Option<Pack> {
+    let path = std::env::args().nth(1)?;
+    let pack = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| Pack::parse(&text).map_err(|e| e.to_string()));
+    match pack {
+        Ok(pack) => Some(pack),
+        Err(e) => {
+            eprintln!("Could not load {}: {}", path, e);
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_pack() -> Option<Pack> {
+    None
 }
@@ -0,0 +1,515 @@
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    board::{Board, BoardState, cmove::CMove, square::Square},
+    solver::Solver,
+};
+
+/// The version of the pack format written by `Pack`'s `Display`. Packs with
+/// a higher version can't be read.
+pub const PACK_VERSION: u32 = 1;
+
+const HEADER: &str = "sol_chess pack";
+
+/// A collection of puzzles, like a set of levels.
+#[derive(Clone, Default)]
+pub struct Pack {
+    pub entries: Vec<PackEntry>,
+}
+
+/// A puzzle in a pack, with what is known about it.
+#[derive(Clone)]
+pub struct PackEntry {
+    pub board: Board,
+    pub title: String,
+    pub author: String,
+
+    /// See `difficulty::rate`.
+    pub difficulty: Option<f64>,
+
+    /// The solutions the puzzle is meant to have, at least one. Other
+    /// solutions may exist.
+    pub solutions: Vec<Vec<CMove>>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    /// The pack doesn't start with `sol_chess pack <version>`, with a version
+    /// of at least 1.
+    MissingHeader,
+
+    /// A pack written with a newer version of the format.
+    UnsupportedVersion(u32),
+
+    /// A line that can't be read, counted from 1.
+    InvalidLine { line: usize, reason: String },
+
+    /// A solution that doesn't win the puzzle. Puzzles are counted from 1.
+    WrongSolution { puzzle: usize, reason: String },
+
+    /// A title, author or tag that would read back differently once written,
+    /// like a title with a line break. Puzzles are counted from 1.
+    InvalidText { puzzle: usize, reason: String },
+}
+
+// The fields of a puzzle as written, with the line they are on.
+#[derive(Default)]
+struct Fields<'a> {
+    line: usize,
+    board: Option<(usize, &'a str)>,
+    title: Option<(usize, &'a str)>,
+    author: Option<(usize, &'a str)>,
+    difficulty: Option<(usize, &'a str)>,
+    solutions: Vec<(usize, &'a str)>,
+    tags: Option<(usize, &'a str)>,
+}
+
+impl Pack {
+    pub fn new() -> Self {
+        Pack::default()
+    }
+
+    /// Reads a pack written like below, and checks it with `validate`. Every
+    /// puzzle needs a board and at least one solution, written in long
+    /// notation on a `solution` line each. Lines starting with a '#' are
+    /// comments.
+    ///
+    /// ```text
+    /// sol_chess pack 1
+    ///
+    /// puzzle
+    /// board N...P.R.K.NP....
+    /// title Rook tour
+    /// author cool-mist
+    /// difficulty 15.38
+    /// tags rooks, long
+    /// solution Rc3xNc2 Rc2xPd2 Rd2xKa2 Ra2xPa3 Ra3xNa4
+    /// solution Rc3xPa3 Ra3xNa4 Ra4xKa2 Ra2xNc2 Rc2xPd2
+    /// ```
+    pub fn parse(pack: &str) -> Result<Self, PackError> {
+        let mut lines = pack
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or(PackError::MissingHeader)?;
+        let version: u32 = header
+            .strip_prefix(HEADER)
+            .filter(|version| version.starts_with(char::is_whitespace))
+            .and_then(|version| version.trim().parse().ok())
+            .filter(|version| *version >= 1)
+            .ok_or(PackError::MissingHeader)?;
+        if version > PACK_VERSION {
+            return Err(PackError::UnsupportedVersion(version));
+        }
+
+        let mut parsed = Pack::new();
+        let mut fields: Option<Fields> = None;
+        for (line, text) in lines {
+            let (key, value) = match text.split_once(char::is_whitespace) {
+                Some((key, value)) => (key, value.trim()),
+                None => (text, ""),
+            };
+
+            if key == "puzzle" {
+                if let Some(fields) = fields.take() {
+                    parsed.entries.push(fields.entry()?);
+                }
+                fields = Some(Fields {
+                    line,
+                    ..Fields::default()
+                });
+                continue;
+            }
+
+            let Some(fields) = fields.as_mut() else {
+                return Err(invalid(line, "puzzles start with a 'puzzle' line"));
+            };
+            if key == "solution" {
+                fields.solutions.push((line, value));
+                continue;
+            }
+
+            let field = match key {
+                "board" => &mut fields.board,
+                "title" => &mut fields.title,
+                "author" => &mut fields.author,
+                "difficulty" => &mut fields.difficulty,
+                "tags" => &mut fields.tags,
+                _ => return Err(invalid(line, "unknown field")),
+            };
+            if field.is_some() {
+                return Err(invalid(line, "the puzzle already has this field"));
+            }
+            *field = Some((line, value));
+        }
+
+        if let Some(fields) = fields {
+            parsed.entries.push(fields.entry()?);
+        }
+
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// Checks every solution against the `Solver`: the puzzle has to have a
+    /// solution, and every move of the stored ones has to be legal and leave
+    /// a position the solver can still win, up to the win. Boards already
+    /// round-trip, as `parse` reads them with `Board::from_string`, but
+    /// titles, authors and tags are checked to be on one line without
+    /// surrounding spaces, and tags to have no commas.
+    pub fn validate(&self) -> Result<(), PackError> {
+        for (index, entry) in self.entries.iter().enumerate() {
+            let text_error = |reason: &str| PackError::InvalidText {
+                puzzle: index + 1,
+                reason: reason.to_string(),
+            };
+            if !is_one_line(&entry.title) {
+                return Err(text_error("the title should be on one line"));
+            }
+            if !is_one_line(&entry.author) {
+                return Err(text_error("the author should be on one line"));
+            }
+            if entry
+                .tags
+                .iter()
+                .any(|tag| tag.is_empty() || tag.contains(',') || !is_one_line(tag))
+            {
+                return Err(text_error("tags should be on one line, without commas"));
+            }
+
+            let wrong = |reason: String| PackError::WrongSolution {
+                puzzle: index + 1,
+                reason,
+            };
+
+            let mut solver = Solver::new(entry.board.clone());
+            if !solver.is_solvable() {
+                return Err(wrong("the puzzle has no solution".to_string()));
+            }
+
+            for solution in &entry.solutions {
+                solver.board = entry.board.clone();
+                for mv in solution {
+                    let played = solver.board.make_move(mv.clone());
+                    played.map_err(|e| wrong(e.to_string()))?;
+                    if !solver.is_solvable() {
+                        return Err(wrong(format!(
+                            "the puzzle can't be won after {}",
+                            mv.long_notation()
+                        )));
+                    }
+                }
+
+                if solver.board.game_state != BoardState::Won {
+                    return Err(wrong(format!(
+                        "{} pieces are left at the end of the solution",
+                        solver.board.num_pieces()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PackEntry {
+    pub fn new(board: Board, solutions: Vec<Vec<CMove>>) -> Self {
+        PackEntry {
+            board,
+            title: String::new(),
+            author: String::new(),
+            difficulty: None,
+            solutions,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Fields<'_> {
+    fn entry(&self) -> Result<PackEntry, PackError> {
+        let (line, board) = self
+            .board
+            .ok_or_else(|| invalid(self.line, "the puzzle has no board"))?;
+        let board =
+            Board::from_string(board.to_string()).map_err(|e| invalid(line, &e.to_string()))?;
+
+        if self.solutions.is_empty() {
+            return Err(invalid(self.line, "the puzzle has no solution"));
+        }
+        let solutions = self
+            .solutions
+            .iter()
+            .map(|(line, solution)| {
+                solution
+                    .split_whitespace()
+                    .map(|mv| parse_move(&board, mv).map_err(|reason| invalid(*line, &reason)))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut entry = PackEntry::new(board, solutions);
+        if let Some((_, title)) = self.title {
+            entry.title = title.to_string();
+        }
+        if let Some((_, author)) = self.author {
+            entry.author = author.to_string();
+        }
+        if let Some((line, difficulty)) = self.difficulty {
+            let difficulty = difficulty
+                .parse()
+                .map_err(|_| invalid(line, "the difficulty should be a number"))?;
+            entry.difficulty = Some(difficulty);
+        }
+        if let Some((_, tags)) = self.tags {
+            entry.tags = tags
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+        }
+
+        Ok(entry)
+    }
+}
+
+/// Reads a move in long notation, like `Rc3xNc2`.
+fn parse_move(board: &Board, notation: &str) -> Result<CMove, String> {
+    let (from, to) = notation
+        .split_once('x')
+        .ok_or_else(|| format!("'{}' should be written like Rc3xNc2", notation))?;
+    let from = Square::parse_with_height(from, board.height()).map_err(|e| e.to_string())?;
+    let to = Square::parse_with_height(to, board.height()).map_err(|e| e.to_string())?;
+    CMove::new(from, to).map_err(|e| e.to_string())
+}
+
+// Whether `text` reads back the same after it's written as a field
+fn is_one_line(text: &str) -> bool {
+    !text.contains(['\n', '\r']) && text.trim() == text
+}
+
+fn invalid(line: usize, reason: &str) -> PackError {
+    PackError::InvalidLine {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+/// Writes the pack the way `Pack::parse` reads it.
+impl Display for Pack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", HEADER, PACK_VERSION)?;
+        for entry in &self.entries {
+            writeln!(f)?;
+            writeln!(f, "puzzle")?;
+            writeln!(f, "board {}", entry.board.board_string())?;
+            if !entry.title.is_empty() {
+                writeln!(f, "title {}", entry.title)?;
+            }
+            if !entry.author.is_empty() {
+                writeln!(f, "author {}", entry.author)?;
+            }
+            if let Some(difficulty) = entry.difficulty {
+                writeln!(f, "difficulty {:.2}", difficulty)?;
+            }
+            if !entry.tags.is_empty() {
+                writeln!(f, "tags {}", entry.tags.join(", "))?;
+            }
+
+            for solution in &entry.solutions {
+                let solution: Vec<String> = solution.iter().map(|m| m.long_notation()).collect();
+                writeln!(f, "solution {}", solution.join(" "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::MissingHeader => {
+                write!(f, "Puzzle packs start with a '{} <version>' line", HEADER)
+            }
+            PackError::UnsupportedVersion(version) => write!(
+                f,
+                "Puzzle pack version {} is newer than the supported version {}",
+                version, PACK_VERSION
+            ),
+            PackError::InvalidLine { line, reason } => {
+                write!(f, "Invalid puzzle pack on line {}: {}", line, reason)
+            }
+            PackError::WrongSolution { puzzle, reason } => {
+                write!(f, "The solution of puzzle {} is wrong: {}", puzzle, reason)
+            }
+            PackError::InvalidText { puzzle, reason } => {
+                write!(
+                    f,
+                    "Puzzle {} can't be written to a pack: {}",
+                    puzzle, reason
+                )
+            }
+        }
+    }
+}
+
+impl Error for PackError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACK: &str = "sol_chess pack 1
+# A comment
+
+puzzle
+board N...P.R.K.NP....
+title Rook tour
+author cool-mist
+difficulty 15.38
+tags rooks, long
+solution Rc3xNc2 Rc2xPd2 Rd2xKa2 Ra2xPa3 Ra3xNa4
+solution Rc3xPa3 Ra3xNa4 Ra4xKa2 Ra2xNc2 Rc2xPd2
+
+puzzle
+board ..N/R.Q/P../...
+solution Qc3xRa3 Qa3xPa2 Qa2xNc4
+";
+
+    #[test]
+    fn pack_round_trip() {
+        let pack = Pack::parse(PACK).unwrap();
+        assert_eq!(2, pack.entries.len());
+
+        let entry = &pack.entries[0];
+        assert_eq!("Rook tour", entry.title);
+        assert_eq!("cool-mist", entry.author);
+        assert_eq!(Some(15.38), entry.difficulty);
+        assert_eq!(vec!["rooks", "long"], entry.tags);
+        assert_eq!(2, entry.solutions.len());
+        assert_eq!("RxNc2", entry.solutions[0][0].notation());
+        assert_eq!("RxPa3", entry.solutions[1][0].notation());
+
+        let entry = &pack.entries[1];
+        assert_eq!((3, 4), (entry.board.width(), entry.board.height()));
+        assert!(entry.title.is_empty());
+        assert_eq!(None, entry.difficulty);
+
+        let written = pack.to_string();
+        assert_eq!(PACK.replace("# A comment\n", ""), written);
+        assert_eq!(written, Pack::parse(&written).unwrap().to_string());
+    }
+
+    #[test]
+    fn pack_errors() {
+        assert_eq!(Some(PackError::MissingHeader), Pack::parse("").err());
+        assert_eq!(
+            Some(PackError::UnsupportedVersion(2)),
+            Pack::parse("sol_chess pack 2").err()
+        );
+        assert!(Pack::parse("sol_chess pack 1").unwrap().entries.is_empty());
+        for header in ["sol_chess pack1", "sol_chess pack 0", "sol_chess packs 1"] {
+            assert_eq!(Some(PackError::MissingHeader), Pack::parse(header).err());
+        }
+
+        let invalid = |line: usize, reason: &str| {
+            Some(PackError::InvalidLine {
+                line,
+                reason: reason.to_string(),
+            })
+        };
+        assert_eq!(
+            invalid(2, "puzzles start with a 'puzzle' line"),
+            Pack::parse("sol_chess pack 1\nboard N...P.R.K.NP....").err()
+        );
+        assert_eq!(
+            invalid(2, "the puzzle has no solution"),
+            Pack::parse("sol_chess pack 1\npuzzle\nboard N...P.R.K.NP....").err()
+        );
+        assert_eq!(
+            invalid(6, "unknown field"),
+            Pack::parse(&PACK.replace("title", "name")).err()
+        );
+        assert_eq!(
+            invalid(8, "the difficulty should be a number"),
+            Pack::parse(&PACK.replace("15.38", "hard")).err()
+        );
+
+        assert_eq!(
+            Some(PackError::WrongSolution {
+                puzzle: 1,
+                reason: "Rc3 can't capture Na4, it doesn't move that way".to_string()
+            }),
+            Pack::parse(&PACK.replace("Rc3xNc2 Rc2xPd2", "Rc3xNa4 Rc2xPd2")).err()
+        );
+        assert_eq!(
+            Some(PackError::WrongSolution {
+                puzzle: 2,
+                reason: "2 pieces are left at the end of the solution".to_string()
+            }),
+            Pack::parse(&PACK.replace(" Qa2xNc4", "")).err()
+        );
+        assert_eq!(
+            Some(PackError::WrongSolution {
+                puzzle: 1,
+                reason: "2 pieces are left at the end of the solution".to_string()
+            }),
+            Pack::parse(&PACK.replace(" Ra2xNc2 Rc2xPd2", " Ra2xNc2")).err()
+        );
+        assert_eq!(
+            Some(PackError::WrongSolution {
+                puzzle: 1,
+                reason: "the puzzle can't be won after Ka2xPa3".to_string()
+            }),
+            Pack::parse(&PACK.replace("Rc3xNc2 Rc2xPd2", "Ka2xPa3 Rc2xPd2")).err()
+        );
+        assert_eq!(
+            Some(PackError::WrongSolution {
+                puzzle: 1,
+                reason: "the puzzle has no solution".to_string()
+            }),
+            Pack::parse("sol_chess pack 1\npuzzle\nboard R..N.........P..\nsolution Ra4xNd4").err()
+        );
+    }
+
+    #[test]
+    fn pack_invalid_text() {
+        let text_error = |reason: &str| {
+            Some(PackError::InvalidText {
+                puzzle: 1,
+                reason: reason.to_string(),
+            })
+        };
+
+        let pack = Pack::parse(PACK).unwrap();
+        let mut titled = pack.clone();
+        titled.entries[0].title = "Rook\ntour".to_string();
+        assert_eq!(
+            text_error("the title should be on one line"),
+            titled.validate().err()
+        );
+
+        let mut authored = pack.clone();
+        authored.entries[0].author = " cool-mist".to_string();
+        assert_eq!(
+            text_error("the author should be on one line"),
+            authored.validate().err()
+        );
+
+        for tag in ["rooks, long", "rooks\nlong", ""] {
+            let mut tagged = pack.clone();
+            tagged.entries[0].tags.push(tag.to_string());
+            assert_eq!(
+                text_error("tags should be on one line, without commas"),
+                tagged.validate().err()
+            );
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
 use crate::board::{
     cmove::CMove,
     {Board, BoardState},
@@ -5,54 +10,265 @@ use crate::board::{
 
 pub struct Solver {
     pub board: Board,
-    moves: Vec<CMove>,
+
+    // Positions already searched, keyed on `Board::id`. Shared by every call
+    // on this solver, so asking twice about the same board is cheap.
+    cache: RefCell<HashMap<u128, CacheEntry>>,
+    hits: Cell<u64>,
+    misses: Cell<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct CacheEntry {
+    solvable: bool,
+    solutions: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    /// Positions that were answered from the cache instead of being searched.
+    pub hits: u64,
+    /// Positions that had to be searched.
+    pub misses: u64,
+    /// Positions currently held in the cache.
+    pub positions: usize,
 }
 
 impl Solver {
     pub fn new(board: Board) -> Solver {
         Solver {
             board,
-            moves: vec![],
+            cache: RefCell::new(HashMap::new()),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
         }
     }
 
-    fn clone(&self, m: CMove) -> Self {
-        let mut moves = self.moves.clone();
-        let mut board = self.board.clone();
-        moves.push(m.clone());
-        board.make_move(m);
-        Solver { board, moves }
+    /// Every sequence of moves that wins the game from `self.board`.
+    ///
+    /// Positions that can't be won are only searched once, however many move
+    /// orders lead to them.
+    pub fn solve(&self) -> Vec<Vec<CMove>> {
+        self.solutions_up_to(usize::MAX)
     }
 
-    pub fn solve(&self) -> Vec<Vec<CMove>> {
+    /// Like `solve`, but stops searching once `limit` solutions are found.
+    pub fn solutions_up_to(&self, limit: usize) -> Vec<Vec<CMove>> {
         let mut solutions = Vec::new();
-        if let BoardState::Won = self.board.game_state {
-            solutions.push(self.moves.clone());
-            return solutions;
+        if limit > 0 {
+            let mut board = self.board.clone();
+            let start = board.history().len();
+            self.collect_solutions(&mut board, start, &mut solutions, limit);
         }
+        solutions
+    }
 
-        let BoardState::InProgress = self.board.game_state else {
-            return solutions;
+    /// Whether the game can be won from `self.board`. Stops searching at the
+    /// first win found.
+    pub fn is_solvable(&self) -> bool {
+        self.solvable_from(&mut self.board.clone())
+    }
+
+    /// Whether `self.board` still has moves left to play, but none of them
+    /// can lead to a win. The board itself only knows it's lost once there
+    /// are no moves left.
+    pub fn is_unwinnable(&self) -> bool {
+        self.board.game_state == BoardState::InProgress && !self.is_solvable()
+    }
+
+    /// The number of sequences of moves that win the game from `self.board`,
+    /// without building any of them. Every position is searched only once.
+    pub fn count_solutions(&self) -> u64 {
+        self.count_from(&mut self.board.clone())
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            positions: self.cache.borrow().len(),
+        }
+    }
+
+    fn collect_solutions(
+        &self,
+        board: &mut Board,
+        start: usize,
+        solutions: &mut Vec<Vec<CMove>>,
+        limit: usize,
+    ) -> u64 {
+        if let BoardState::Won = board.game_state {
+            solutions.push(board.history()[start..].to_vec());
+            return 1;
+        }
+
+        let BoardState::InProgress = board.game_state else {
+            return 0;
         };
 
-        self.board.legal_moves.iter().for_each(|m| {
-            let solver = self.clone(m.clone());
-            let more_solutions = solver.solve();
-            solutions.extend(more_solutions);
+        if let Some(entry) = self.lookup(board)
+            && !entry.solvable
+        {
+            self.hits.set(self.hits.get() + 1);
+            return 0;
+        }
+
+        self.misses.set(self.misses.get() + 1);
+        let mut found = 0;
+        for m in legal_moves(board) {
+            if solutions.len() >= limit {
+                break;
+            }
+
+            board.play(m);
+            found += self.collect_solutions(board, start, solutions, limit);
+            board.unmake_move();
+        }
+
+        // Once the limit is reached, this position or one below it may have
+        // stopped early, so `found` can't be trusted as the full count. Only
+        // solutions found below this position fill up the limit, so it is
+        // still known to be solvable.
+        let complete = solutions.len() < limit;
+        if complete {
+            self.store(
+                board,
+                CacheEntry {
+                    solvable: found > 0,
+                    solutions: Some(found),
+                },
+            );
+        } else if self.lookup(board).is_none() {
+            self.store(
+                board,
+                CacheEntry {
+                    solvable: true,
+                    solutions: None,
+                },
+            );
+        }
+
+        found
+    }
+
+    fn solvable_from(&self, board: &mut Board) -> bool {
+        match board.game_state {
+            BoardState::Won => return true,
+            BoardState::InProgress => {}
+            _ => return false,
+        }
+
+        if let Some(entry) = self.lookup(board) {
+            self.hits.set(self.hits.get() + 1);
+            return entry.solvable;
+        }
+
+        self.misses.set(self.misses.get() + 1);
+        let solvable = legal_moves(board).into_iter().any(|m| {
+            board.play(m);
+            let solvable = self.solvable_from(board);
+            board.unmake_move();
+            solvable
         });
 
-        solutions
+        self.store(
+            board,
+            CacheEntry {
+                solvable,
+                solutions: if solvable { None } else { Some(0) },
+            },
+        );
+        solvable
+    }
+
+    fn count_from(&self, board: &mut Board) -> u64 {
+        match board.game_state {
+            BoardState::Won => return 1,
+            BoardState::InProgress => {}
+            _ => return 0,
+        }
+
+        if let Some(solutions) = self.lookup(board).and_then(|entry| entry.solutions) {
+            self.hits.set(self.hits.get() + 1);
+            return solutions;
+        }
+
+        self.misses.set(self.misses.get() + 1);
+        let mut found = 0;
+        for m in legal_moves(board) {
+            board.play(m);
+            found += self.count_from(board);
+            board.unmake_move();
+        }
+
+        self.store(
+            board,
+            CacheEntry {
+                solvable: found > 0,
+                solutions: Some(found),
+            },
+        );
+        found
+    }
+
+    fn lookup(&self, board: &Board) -> Option<CacheEntry> {
+        if !board.has_unique_id() {
+            return None;
+        }
+
+        self.cache.borrow().get(&board.id()).copied()
+    }
+
+    fn store(&self, board: &Board, entry: CacheEntry) {
+        if !board.has_unique_id() {
+            return;
+        }
+
+        self.cache.borrow_mut().insert(board.id(), entry);
     }
 }
 
+/// Puts solutions that only differ in the order of captures that don't depend
+/// on each other into the same group. Two solutions are in the same group when
+/// they are made of the same captures. Groups keep the order in which their
+/// first solution appears in `solutions`.
+pub fn group_equivalent(solutions: Vec<Vec<CMove>>) -> Vec<Vec<Vec<CMove>>> {
+    let mut groups: Vec<Vec<Vec<CMove>>> = Vec::new();
+    let mut group_of: HashMap<Vec<CMove>, usize> = HashMap::new();
+    for solution in solutions {
+        let mut captures = solution.clone();
+        captures.sort_by_key(|m| (m.from.file, m.from.rank, m.to.file, m.to.rank, m.notation()));
+        match group_of.get(&captures) {
+            Some(&index) => groups[index].push(solution),
+            None => {
+                group_of.insert(captures, groups.len());
+                groups.push(vec![solution]);
+            }
+        }
+    }
+
+    groups
+}
+
+// Moves are made and taken back on the board while searching, so its legal
+// moves need copying out first
+/// The legal moves on `board`, ordered by the squares they move from and to
+/// so solutions always come out in the same order.
+fn legal_moves(board: &Board) -> Vec<CMove> {
+    let mut moves: Vec<CMove> = board.legal_moves.iter().cloned().collect();
+    moves.sort_by_key(|m| (m.from.file, m.from.rank, m.to.file, m.to.rank));
+    moves
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::{square::Square, Board};
+    use crate::board::{Board, square::Square};
 
     macro_rules! sq {
         ($sq:literal) => {
-            Square::parse($sq)
+            Square::parse($sq).unwrap()
         };
     }
 
@@ -80,7 +296,7 @@ mod tests {
             let mut board = board.clone();
             solution
                 .into_iter()
-                .for_each(|m| assert!(board.make_move(m).is_some()));
+                .for_each(|m| assert!(board.make_move(m).is_ok()));
             assert_eq!(BoardState::Won, board.game_state);
         }
     }
@@ -106,4 +322,155 @@ mod tests {
 
         assert_eq!(0, solutions.len());
     }
+
+    #[test]
+    fn solver_cache() {
+        // P . . P
+        // . P P .
+        // . P P .
+        // P . . P
+        let board = Board::from_string("P..P.PP..PP.P..P".to_string()).unwrap();
+        let solver = Solver::new(board);
+        assert_eq!(0, solver.solve().len());
+
+        // The middle pawns can capture each other in several orders, which
+        // all end up in the same position
+        let stats = solver.cache_stats();
+        assert!(stats.hits > 0);
+        assert!(stats.positions > 0);
+
+        // A second solve only needs the top position from the cache
+        assert_eq!(0, solver.solve().len());
+        let again = solver.cache_stats();
+        assert_eq!(stats.hits + 1, again.hits);
+        assert_eq!(stats.misses, again.misses);
+    }
+
+    #[test]
+    fn solver_count_solutions() {
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let solver = Solver::new(board);
+        assert_eq!(3, solver.count_solutions());
+
+        // Counting again is answered straight from the cache
+        let misses = solver.cache_stats().misses;
+        assert_eq!(3, solver.count_solutions());
+        assert_eq!(misses, solver.cache_stats().misses);
+        assert_eq!(3, solver.solve().len());
+    }
+
+    #[test]
+    fn solver_stable_order() {
+        let solutions = || {
+            let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+            Solver::new(board)
+                .solve()
+                .iter()
+                .map(|s| s.iter().map(|m| m.long_notation()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        let first = solutions();
+        for _ in 0..10 {
+            assert_eq!(first, solutions());
+        }
+    }
+
+    #[test]
+    fn solver_unwinnable() {
+        // N . . .
+        // P . R .
+        // K . N P
+        // . . . .
+        let mut board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        assert!(!Solver::new(board.clone()).is_unwinnable());
+
+        // Taking the rook with the pawn leaves no way to win
+        board
+            .make_move(CMove::new(sq!("Pd2"), sq!("Rc3")).unwrap())
+            .unwrap();
+        assert_eq!(BoardState::InProgress, board.game_state);
+        assert!(Solver::new(board.clone()).is_unwinnable());
+
+        // Once the board knows it's lost, it's no longer just unwinnable
+        let mut board = Board::from_string("K..P............".to_string()).unwrap();
+        assert_eq!(BoardState::Lost, board.game_state);
+        assert!(!Solver::new(board.clone()).is_unwinnable());
+
+        board.set(sq!(".d4"));
+        assert_eq!(BoardState::Won, board.game_state);
+        assert!(!Solver::new(board).is_unwinnable());
+    }
+
+    #[test]
+    fn solver_after_moves() {
+        let mut board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        board
+            .make_move(CMove::new(sq!("Rc3"), sq!("Nc2")).unwrap())
+            .unwrap();
+
+        // Solutions start from the current position, not from the first move
+        // in the board's history
+        let solutions = Solver::new(board.clone()).solve();
+        assert!(!solutions.is_empty());
+        for solution in solutions {
+            assert_eq!(4, solution.len());
+        }
+        assert_eq!(1, board.history().len());
+    }
+
+    #[test]
+    fn solver_early_exit() {
+        let board = Board::from_string("N...P.R.K.NP....".to_string()).unwrap();
+        let solver = Solver::new(board.clone());
+        assert!(solver.is_solvable());
+        assert_eq!(0, solver.solutions_up_to(0).len());
+        assert_eq!(1, solver.solutions_up_to(1).len());
+        assert_eq!(2, solver.solutions_up_to(2).len());
+        assert_eq!(3, solver.solutions_up_to(10).len());
+
+        // Stopping early must not leave wrong counts behind in the cache
+        assert_eq!(3, solver.count_solutions());
+
+        let solver = Solver::new(board);
+        assert!(solver.is_solvable());
+        assert_eq!(3, solver.count_solutions());
+
+        let board = Board::from_string("QPPR............".to_string()).unwrap();
+        for limit in 1..8 {
+            let solver = Solver::new(board.clone());
+            assert_eq!(limit, solver.solutions_up_to(limit).len());
+            assert_eq!(8, solver.count_solutions());
+        }
+
+        let board = Board::from_string("P..P.PP..PP.P..P".to_string()).unwrap();
+        let solver = Solver::new(board);
+        assert!(!solver.is_solvable());
+        assert!(solver.is_unwinnable());
+        assert_eq!(0, solver.count_solutions());
+        assert_eq!(0, solver.solutions_up_to(5).len());
+    }
+
+    #[test]
+    fn solver_group_equivalent() {
+        // Q P P R
+        // . . . .
+        // . . . .
+        // . . . .
+        let board = Board::from_string("QPPR............".to_string()).unwrap();
+        let solutions = Solver::new(board).solve();
+        assert_eq!(8, solutions.len());
+
+        // The queen and the rook can each take their pawn in either order
+        let groups = group_equivalent(solutions);
+        assert_eq!(6, groups.len());
+        assert_eq!(2, groups.iter().filter(|g| g.len() == 2).count());
+        for group in groups {
+            let first = &group[0];
+            for solution in &group {
+                assert_eq!(first.len(), solution.len());
+                assert!(solution.iter().all(|m| first.contains(m)));
+            }
+        }
+    }
 }